@@ -0,0 +1,31 @@
+#![no_main]
+
+// Arbitrary input must never make the file header, section header and
+// symbol table parsers panic, corrupted files are reported as errors or
+// displayed with the damaged parts left out
+use libfuzzer_sys::fuzz_target;
+use rust_elf::file::ElfFileHeader;
+use rust_elf::reader::Reader;
+use rust_elf::section::SectionHeaders;
+use rust_elf::symbols::SymbolTables;
+
+fuzz_target!(|data: &[u8]| {
+    let mut reader = Reader::new(data.to_vec());
+
+    let header = match ElfFileHeader::new(&mut reader) {
+        Ok(header) => header,
+        Err(_) => return,
+    };
+
+    let sections = match SectionHeaders::new(&header, &mut reader) {
+        Ok(sections) => sections,
+        Err(_) => return,
+    };
+
+    // names and values are resolved only when displayed
+    let _ = format!("{}", sections);
+
+    if let Ok(symbols) = SymbolTables::new(&sections, &header, &mut reader) {
+        let _ = format!("{}", symbols);
+    }
+});
@@ -0,0 +1,152 @@
+use crate::error::Result;
+use crate::reader::{Cursor, Reader, ReaderExt, Seek, SeekFrom};
+use crate::section::{SectionHeader, SectionHeaderType, SectionHeaders};
+use crate::symbols::SymbolTable;
+use std::fmt;
+use std::io::Read;
+
+// Address-significance table (.llvm_addrsig), sequence of ULEB128
+// encoded indices into the symbol table referenced by sh_link. Symbols
+// listed here must not be merged by identical code folding.
+#[derive(Debug)]
+struct AddrsigSection {
+    // Symbol index and name
+    data: Vec<(u64, String)>,
+    name: String,
+}
+
+#[derive(Debug)]
+pub struct AddrsigSections {
+    data: Vec<AddrsigSection>,
+}
+
+impl AddrsigSection {
+    fn new(
+        headers: &SectionHeaders,
+        header: &SectionHeader,
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<AddrsigSection> {
+        reader.check_range(header.sh_offset, header.sh_size)?;
+        reader.seek(SeekFrom::Start(header.sh_offset))?;
+
+        let mut buffer = vec![0; header.sh_size as usize];
+        reader.read_exact(&mut buffer)?;
+
+        let symtab_header = headers.try_get_by_index(header.sh_link as usize)?;
+        let symtab = SymbolTable::new(headers, &symtab_header, machine, reader)?;
+
+        let mut reader = Cursor::new(buffer);
+        let mut data = vec![];
+
+        while reader.position() < header.sh_size {
+            let index = reader.read_uleb128()?;
+
+            let name = if (index as usize) < symtab.len() {
+                symtab.get_by_index(index as usize).0
+            } else {
+                String::from("<invalid>")
+            };
+
+            data.push((index, name));
+        }
+
+        Ok(AddrsigSection {
+            data,
+            name: headers.strtab.try_get(header.sh_name as u64)?,
+        })
+    }
+}
+
+impl AddrsigSections {
+    pub fn new(
+        headers: &SectionHeaders,
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<AddrsigSections> {
+        let mut data = vec![];
+
+        for header in &headers.get_all(SectionHeaderType::LlvmAddrSig) {
+            data.push(AddrsigSection::new(headers, header, machine, reader)?);
+        }
+
+        Ok(AddrsigSections { data })
+    }
+}
+
+impl fmt::Display for AddrsigSection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Address-significance section `{}' contains {} entries:",
+            self.name,
+            self.data.len()
+        )?;
+        writeln!(f, "{:<6} Name", "Ndx")?;
+
+        for (index, name) in &self.data {
+            writeln!(f, "{:<06} {}", index, name)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AddrsigSections {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for section in &self.data {
+            section.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, symbol, ElfBuilder, Section};
+
+    // SHT_SYMTAB, SHT_STRTAB, SHT_LLVM_ADDRSIG
+    const SYMTAB: u32 = 2;
+    const STRTAB: u32 = 3;
+    const ADDRSIG: u32 = 0x6fff4c03;
+
+    fn addrsig(indices: Vec<u8>) -> Result<AddrsigSections> {
+        let mut data = symbol(0, 0, 0, 0, 0);
+        data.extend(symbol(1, 0x12, 1, 0x1000, 16));
+
+        let mut symtab = Section::new(".symtab", SYMTAB, data);
+        symtab.sh_link = 2;
+        symtab.sh_entsize = 24;
+
+        let mut addrsig = Section::new(".llvm_addrsig", ADDRSIG, indices);
+        addrsig.sh_link = 1;
+
+        // REL, x86-64
+        let data = ElfBuilder::new(1, 62)
+            .section(symtab)
+            .section(Section::new(".strtab", STRTAB, b"\0main\0".to_vec()))
+            .section(addrsig)
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader)?;
+
+        AddrsigSections::new(&sections, 62, &mut reader)
+    }
+
+    #[test]
+    fn reads_indices() {
+        // the second index (ULEB128 encoded 130) is past the symbol table
+        let addrsig = addrsig(vec![0x01, 0x82, 0x01]).unwrap();
+
+        assert_eq!(
+            addrsig.data[0].data,
+            vec![(1, "main".to_string()), (130, "<invalid>".to_string())]
+        );
+    }
+
+    #[test]
+    fn truncated_index() {
+        assert!(addrsig(vec![0x01, 0x80]).unwrap_err().is_eof());
+    }
+}
@@ -0,0 +1,424 @@
+use crate::error::{ElfError, Result};
+use crate::reader::{Cursor, LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
+use crate::section::{SectionHeader, SectionHeaderType, SectionHeaders};
+use std::fmt;
+use std::io::Read;
+
+// Attributes format version, the only one defined so far
+const ATTRIBUTES_FORMAT_VERSION: u8 = b'A';
+
+fn arm_attribute_tag(tag: u64) -> Option<&'static str> {
+    match tag {
+        4 => Some("Tag_CPU_raw_name"),
+        5 => Some("Tag_CPU_name"),
+        6 => Some("Tag_CPU_arch"),
+        7 => Some("Tag_CPU_arch_profile"),
+        8 => Some("Tag_ARM_ISA_use"),
+        9 => Some("Tag_THUMB_ISA_use"),
+        10 => Some("Tag_FP_arch"),
+        11 => Some("Tag_WMMX_arch"),
+        12 => Some("Tag_Advanced_SIMD_arch"),
+        13 => Some("Tag_PCS_config"),
+        14 => Some("Tag_ABI_PCS_R9_use"),
+        15 => Some("Tag_ABI_PCS_RW_data"),
+        16 => Some("Tag_ABI_PCS_RO_data"),
+        17 => Some("Tag_ABI_PCS_GOT_use"),
+        18 => Some("Tag_ABI_PCS_wchar_t"),
+        19 => Some("Tag_ABI_FP_rounding"),
+        20 => Some("Tag_ABI_FP_denormal"),
+        21 => Some("Tag_ABI_FP_exceptions"),
+        22 => Some("Tag_ABI_FP_user_exceptions"),
+        23 => Some("Tag_ABI_FP_number_model"),
+        24 => Some("Tag_ABI_align_needed"),
+        25 => Some("Tag_ABI_align_preserved"),
+        26 => Some("Tag_ABI_enum_size"),
+        27 => Some("Tag_ABI_HardFP_use"),
+        28 => Some("Tag_ABI_VFP_args"),
+        29 => Some("Tag_ABI_WMMX_args"),
+        30 => Some("Tag_ABI_optimization_goals"),
+        31 => Some("Tag_ABI_FP_optimization_goals"),
+        32 => Some("Tag_compatibility"),
+        34 => Some("Tag_CPU_unaligned_access"),
+        36 => Some("Tag_FP_HP_extension"),
+        38 => Some("Tag_ABI_FP_16bit_format"),
+        42 => Some("Tag_MPextension_use"),
+        44 => Some("Tag_DIV_use"),
+        46 => Some("Tag_DSP_extension"),
+        64 => Some("Tag_nodefaults"),
+        65 => Some("Tag_also_compatible_with"),
+        66 => Some("Tag_T2EE_use"),
+        67 => Some("Tag_conformance"),
+        68 => Some("Tag_Virtualization_use"),
+        70 => Some("Tag_MPextension_use"),
+        _ => None,
+    }
+}
+
+fn arm_cpu_arch(value: u64) -> &'static str {
+    match value {
+        0 => "Pre-v4",
+        1 => "v4",
+        2 => "v4T",
+        3 => "v5T",
+        4 => "v5TE",
+        5 => "v5TEJ",
+        6 => "v6",
+        7 => "v6KZ",
+        8 => "v6T2",
+        9 => "v6K",
+        10 => "v7",
+        11 => "v6-M",
+        12 => "v6S-M",
+        13 => "v7E-M",
+        14 => "v8",
+        15 => "v8-R",
+        16 => "v8-M.baseline",
+        17 => "v8-M.mainline",
+        21 => "v8.1-M.mainline",
+        _ => "Unknown",
+    }
+}
+
+fn arm_cpu_arch_profile(value: u64) -> &'static str {
+    match value {
+        0 => "None",
+        0x41 => "Application",
+        0x52 => "Realtime",
+        0x4d => "Microcontroller",
+        0x53 => "Application or Realtime",
+        _ => "Unknown",
+    }
+}
+
+#[derive(Debug)]
+enum AttributeValue {
+    // ULEB128 encoded integer
+    Integer(u64),
+    // Null terminated string
+    String(String),
+    // Tag_compatibility: flag followed by a vendor name
+    Compatibility(u64, String),
+}
+
+#[derive(Debug)]
+struct Attribute {
+    tag: u64,
+    // Tag name, known only for the ARM EABI vendor
+    name: Option<&'static str>,
+    value: AttributeValue,
+}
+
+#[derive(Debug)]
+enum AttributeScope {
+    // Attributes apply to the whole file
+    File,
+    // Attributes apply to listed sections
+    Section(Vec<u64>),
+    // Attributes apply to listed symbols
+    Symbol(Vec<u64>),
+    // Unknown
+    Unknown(u64),
+}
+
+#[derive(Debug)]
+struct AttributeSubsection {
+    // Name of the vendor that defines the attributes
+    vendor: String,
+    // Which entities are attributes applied to
+    scope: AttributeScope,
+    attributes: Vec<Attribute>,
+}
+
+#[derive(Debug)]
+struct AttributesSection {
+    data: Vec<AttributeSubsection>,
+    name: String,
+}
+
+#[derive(Debug)]
+pub struct AttributesSections {
+    data: Vec<AttributesSection>,
+}
+
+impl Attribute {
+    fn new(vendor: &str, reader: &mut Reader) -> Result<Attribute> {
+        let tag = reader.read_uleb128()?;
+
+        let value = match (vendor, tag) {
+            ("aeabi", 4) | ("aeabi", 5) | ("aeabi", 67) => {
+                AttributeValue::String(reader.read_cstr_at(reader.position())?)
+            }
+            ("aeabi", 32) => AttributeValue::Compatibility(
+                reader.read_uleb128()?,
+                reader.read_cstr_at(reader.position())?,
+            ),
+            // Generic rule: tags above 32 that are odd hold strings
+            (_, tag) if tag > 32 && tag % 2 == 1 => {
+                AttributeValue::String(reader.read_cstr_at(reader.position())?)
+            }
+            _ => AttributeValue::Integer(reader.read_uleb128()?),
+        };
+
+        let name = if vendor == "aeabi" {
+            arm_attribute_tag(tag)
+        } else {
+            None
+        };
+
+        Ok(Attribute { tag, name, value })
+    }
+}
+
+impl AttributeSubsection {
+    fn new(vendor: &str, end: u64, reader: &mut Reader) -> Result<AttributeSubsection> {
+        let start = reader.position();
+        let kind = reader.read_uleb128()?;
+        let size = reader.read_u32::<LittleEndian>()? as u64;
+
+        let end = if start + size > end {
+            end
+        } else {
+            start + size
+        };
+
+        let read_indices = |reader: &mut Reader| -> Result<Vec<u64>> {
+            let mut indices = vec![];
+
+            loop {
+                let index = reader.read_uleb128()?;
+
+                if index == 0 {
+                    break;
+                }
+                indices.push(index);
+            }
+            Ok(indices)
+        };
+
+        let scope = match kind {
+            1 => AttributeScope::File,
+            2 => AttributeScope::Section(read_indices(reader)?),
+            3 => AttributeScope::Symbol(read_indices(reader)?),
+            _ => AttributeScope::Unknown(kind),
+        };
+
+        let mut attributes = vec![];
+
+        if let AttributeScope::Unknown(_) = scope {
+            reader.seek(SeekFrom::Start(end))?;
+        }
+
+        while reader.position() < end {
+            attributes.push(Attribute::new(vendor, reader)?);
+        }
+
+        Ok(AttributeSubsection {
+            vendor: vendor.to_string(),
+            scope,
+            attributes,
+        })
+    }
+}
+
+impl AttributesSection {
+    fn new(header: &SectionHeader, name: String, reader: &mut Reader) -> Result<AttributesSection> {
+        reader.check_range(header.sh_offset, header.sh_size)?;
+        reader.seek(SeekFrom::Start(header.sh_offset))?;
+
+        let mut buffer = vec![0; header.sh_size as usize];
+        reader.read_exact(&mut buffer)?;
+
+        let mut reader = Cursor::new(buffer);
+        let version = reader.read_u8()?;
+
+        if version != ATTRIBUTES_FORMAT_VERSION {
+            return Err(ElfError::Malformed(format!(
+                "attributes format version: {:#x}",
+                version
+            )));
+        }
+
+        let mut data = vec![];
+
+        // Each vendor section: length (including itself), vendor name
+        // and a sequence of tagged subsections
+        while reader.position() < header.sh_size {
+            let start = reader.position();
+            let length = reader.read_u32::<LittleEndian>()? as u64;
+
+            if length < 4 {
+                return Err(ElfError::Malformed(format!(
+                    "attributes subsection length: {}",
+                    length
+                )));
+            }
+
+            let end = if start + length > header.sh_size {
+                header.sh_size
+            } else {
+                start + length
+            };
+
+            let vendor = reader.read_cstr_at(reader.position())?;
+
+            while reader.position() < end {
+                data.push(AttributeSubsection::new(&vendor, end, &mut reader)?);
+            }
+
+            reader.seek(SeekFrom::Start(end))?;
+        }
+
+        Ok(AttributesSection { data, name })
+    }
+}
+
+impl AttributesSections {
+    pub fn new(headers: &SectionHeaders, reader: &mut Reader) -> Result<AttributesSections> {
+        let mut data = vec![];
+
+        for header in &headers.headers {
+            let name = headers.strtab.get(header.sh_name as u64);
+
+            // Processor specific attribute sections (e.g. `.ARM.attributes')
+            // don't share the section type, so match them by name
+            if header.sh_type == SectionHeaderType::GnuAttributes || name.ends_with(".attributes") {
+                data.push(AttributesSection::new(header, name, reader)?);
+            }
+        }
+
+        Ok(AttributesSections { data })
+    }
+}
+
+impl fmt::Display for Attribute {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.name {
+            Some(name) => write!(f, "  {}: ", name)?,
+            None => write!(f, "  Tag_unknown_{}: ", self.tag)?,
+        }
+
+        match &self.value {
+            AttributeValue::Integer(value) => match self.name {
+                Some("Tag_CPU_arch") => writeln!(f, "{}", arm_cpu_arch(*value)),
+                Some("Tag_CPU_arch_profile") => writeln!(f, "{}", arm_cpu_arch_profile(*value)),
+                _ => writeln!(f, "{}", value),
+            },
+            AttributeValue::String(value) => writeln!(f, "\"{}\"", value),
+            AttributeValue::Compatibility(flag, vendor) => {
+                writeln!(f, "flag = {}, vendor = {}", flag, vendor)
+            }
+        }
+    }
+}
+
+impl fmt::Display for AttributeSubsection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Vendor: {}", self.vendor)?;
+
+        match &self.scope {
+            AttributeScope::File => writeln!(f, "File Attributes")?,
+            AttributeScope::Section(indices) => writeln!(f, "Section Attributes: {:?}", indices)?,
+            AttributeScope::Symbol(indices) => writeln!(f, "Symbol Attributes: {:?}", indices)?,
+            AttributeScope::Unknown(kind) => writeln!(f, "Unknown Attributes: {}", kind)?,
+        }
+
+        for attribute in &self.attributes {
+            attribute.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for AttributesSection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Attribute section `{}':", self.name)?;
+
+        for subsection in &self.data {
+            subsection.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for AttributesSections {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for section in &self.data {
+            section.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, ElfBuilder, Section};
+
+    // SHT_ARM_ATTRIBUTES
+    const ARM_ATTRIBUTES: u32 = 0x70000003;
+
+    // File attributes of the `aeabi' vendor with the given vendor and
+    // subsection lengths, the right ones are used when they are None
+    fn aeabi(version: u8, length: Option<u32>, size: Option<u32>) -> Vec<u8> {
+        // Tag_CPU_name "cortex-a9", Tag_CPU_arch v7
+        let mut attributes = vec![5];
+        attributes.extend(b"cortex-a9\0");
+        attributes.extend(&[6, 10]);
+
+        let size = size.unwrap_or(5 + attributes.len() as u32);
+        let length = length.unwrap_or(4 + 6 + size);
+
+        let mut data = vec![version];
+        data.extend(&length.to_le_bytes());
+        data.extend(b"aeabi\0");
+        data.push(1);
+        data.extend(&size.to_le_bytes());
+        data.extend(attributes);
+
+        data
+    }
+
+    fn attributes(data: Vec<u8>) -> Result<AttributesSections> {
+        // REL, ARM
+        let data = ElfBuilder::new(1, 40)
+            .section(Section::new(".ARM.attributes", ARM_ATTRIBUTES, data))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader)?;
+
+        AttributesSections::new(&sections, &mut reader)
+    }
+
+    const CORTEX_A9: &str = "Attribute section `.ARM.attributes':
+Vendor: aeabi
+File Attributes
+  Tag_CPU_name: \"cortex-a9\"
+  Tag_CPU_arch: v7
+";
+
+    #[test]
+    fn reads_aeabi() {
+        let attributes = attributes(aeabi(b'A', None, None)).unwrap();
+
+        assert_eq!(attributes.to_string(), CORTEX_A9);
+    }
+
+    #[test]
+    fn length_past_section_end() {
+        // both lengths are cut at the end of the section
+        let attributes = attributes(aeabi(b'A', Some(0x1000), Some(0x1000))).unwrap();
+
+        assert_eq!(attributes.to_string(), CORTEX_A9);
+    }
+
+    #[test]
+    fn bad_format_version() {
+        match attributes(aeabi(b'B', None, None)) {
+            Err(ElfError::Malformed(what)) => assert_eq!(what, "attributes format version: 0x42"),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::debuglink::{crc32, read_section};
+use crate::error::Result;
+use crate::reader::Reader;
+use crate::section::{SectionHeaderType, SectionHeaders};
+use std::fmt;
+
+// Checksum of a section contents
+#[derive(Debug)]
+struct SectionChecksum {
+    name: String,
+    kind: SectionHeaderType,
+    size: u64,
+    crc: u32,
+    // Uncompressed size of SHF_COMPRESSED sections, the checksum is
+    // computed from the compressed data
+    uncompressed: Option<u64>,
+}
+
+// CRC32 of contents of every section with data in the file, used to
+// find sections that differ between two builds
+#[derive(Debug)]
+pub struct SectionChecksums {
+    data: Vec<SectionChecksum>,
+}
+
+impl SectionChecksums {
+    pub fn new(headers: &SectionHeaders, reader: &mut Reader) -> Result<SectionChecksums> {
+        let mut data = vec![];
+
+        for (i, header) in headers.headers.iter().enumerate() {
+            if header.sh_type == SectionHeaderType::Null || header.sh_type == SectionHeaderType::Bss
+            {
+                continue;
+            }
+
+            let contents = read_section(header, reader)?;
+
+            data.push(SectionChecksum {
+                name: headers.strtab.get(header.sh_name as u64),
+                kind: header.sh_type.clone(),
+                size: header.sh_size,
+                crc: crc32(&contents),
+                uncompressed: headers.uncompressed_size(i),
+            });
+        }
+
+        Ok(SectionChecksums { data })
+    }
+}
+
+impl fmt::Display for SectionChecksums {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<24} {:<16} {:<18} CRC32", "Name", "Type", "Size")?;
+
+        for section in &self.data {
+            write!(
+                f,
+                "{:<24} {:<16} {:#018x} {:08x}",
+                section.name,
+                section.kind.to_string(),
+                section.size,
+                section.crc
+            )?;
+
+            if let Some(size) = section.uncompressed {
+                write!(f, " (compressed, {:#x} bytes uncompressed)", size)?;
+            }
+
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
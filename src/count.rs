@@ -0,0 +1,70 @@
+use crate::dynamic::DynamicSection;
+use crate::notes::NoteSections;
+use crate::program::ProgramHeaders;
+use crate::relocs::RelocationSections;
+use crate::section::SectionHeaders;
+use crate::symbols::SymbolTables;
+use crate::version::VersionSection;
+use std::fmt;
+
+// Number of entries in each of the parsed tables
+#[derive(Debug)]
+pub struct TableCounts {
+    data: Vec<(String, usize)>,
+}
+
+impl TableCounts {
+    pub fn new(
+        programs: &ProgramHeaders,
+        sections: &SectionHeaders,
+        symbols: &SymbolTables,
+        dynamic: Option<&DynamicSection>,
+        relocs: &RelocationSections,
+        notes: &NoteSections,
+        version: Option<&VersionSection>,
+    ) -> TableCounts {
+        let mut data = vec![
+            (String::from("Program headers"), programs.headers.len()),
+            (String::from("Section headers"), sections.headers.len()),
+        ];
+
+        for (name, count) in symbols.counts() {
+            data.push((format!("Symbols in {}", name), count));
+        }
+
+        if let Some(dynamic) = dynamic {
+            data.push((String::from("Dynamic entries"), dynamic.count()));
+        }
+
+        for section in &relocs.sections {
+            data.push((
+                format!("Relocations in {}", section.name),
+                section.entries.len(),
+            ));
+        }
+
+        for section in &relocs.relr {
+            data.push((
+                format!("Relocations in {}", section.name),
+                section.offsets.len(),
+            ));
+        }
+
+        data.push((String::from("Notes"), notes.count()));
+
+        if let Some(version) = version {
+            data.push((String::from("Version needs"), version.count()));
+        }
+
+        TableCounts { data }
+    }
+}
+
+impl fmt::Display for TableCounts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, count) in &self.data {
+            writeln!(f, "{}: {}", name, count)?;
+        }
+        Ok(())
+    }
+}
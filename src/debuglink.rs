@@ -0,0 +1,358 @@
+use crate::error::{ElfError, Result};
+use crate::reader::{Reader, ReaderExt, Seek, SeekFrom};
+use crate::section::{SectionHeader, SectionHeaderType, SectionHeaders};
+use std::fmt;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// Section occupies memory during execution
+const SHF_ALLOC: u64 = 1 << 1;
+
+// Global debug directory searched by debuggers
+pub const DEFAULT_DEBUG_DIR: &str = "/usr/lib/debug";
+
+pub fn read_section(header: &SectionHeader, reader: &mut Reader) -> Result<Vec<u8>> {
+    let size = header.file_size();
+
+    reader.check_range(header.sh_offset, size)?;
+    reader.seek(SeekFrom::Start(header.sh_offset))?;
+
+    let mut data = vec![0; size as usize];
+    reader.read_exact(&mut data)?;
+
+    Ok(data)
+}
+
+// Split name from the rest of data, name is null terminated
+fn split_name(data: &[u8]) -> Result<(String, &[u8])> {
+    let end = data
+        .iter()
+        .position(|ch| *ch == 0)
+        .ok_or_else(|| ElfError::Malformed("debug link file name".to_string()))?;
+
+    let name = String::from_utf8(data[..end].to_vec())?;
+
+    Ok((name, &data[end + 1..]))
+}
+
+// CRC32 (polynomial 0xedb88320) as used by `gnu_debuglink_crc32'
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc: u32 = 0xffff_ffff;
+
+    for byte in data {
+        crc ^= *byte as u32;
+
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+
+    !crc
+}
+
+// Debug only files keep notes, but the contents of other allocated
+// sections are stripped (they are turned into NOBITS)
+fn is_debug_only(headers: &SectionHeaders) -> bool {
+    let mut allocated = headers
+        .headers
+        .iter()
+        .filter(|header| header.sh_flags & SHF_ALLOC == SHF_ALLOC)
+        .filter(|header| header.sh_type != SectionHeaderType::Note)
+        .peekable();
+
+    let has_debug_info = headers.get_by_name(".debug_info").is_some();
+
+    has_debug_info
+        && allocated.peek().is_some()
+        && allocated.all(|header| header.sh_type == SectionHeaderType::Bss)
+}
+
+// Contents of the `.gnu_debuglink' section, name of the separate
+// debug file followed by its CRC32
+#[derive(Debug)]
+pub struct DebugLink {
+    pub filename: String,
+    pub crc: u32,
+}
+
+// Contents of the `.gnu_debugaltlink' section, name of the
+// supplementary debug file (dwz) followed by its build-id
+#[derive(Debug)]
+pub struct DebugAltLink {
+    pub filename: String,
+    pub build_id: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum DebugLinkStatus {
+    // File has no `.gnu_debuglink' section
+    NoLink,
+    // Companion file wasn't found in any of the searched paths
+    NotFound(Vec<PathBuf>),
+    // Companion file found, but its CRC differs
+    Mismatch {
+        path: PathBuf,
+        expected: u32,
+        actual: u32,
+    },
+    // Companion file found and its CRC matches
+    Match(PathBuf),
+}
+
+// Separate debug file named after the build-id, debuggers look for
+// `<debug-dir>/.build-id/ab/cdef....debug'
+#[derive(Debug)]
+pub struct BuildIdDebugFile {
+    build_id: Option<String>,
+    // Searched path and size of the file if it exists
+    candidates: Vec<(PathBuf, Option<u64>)>,
+}
+
+#[derive(Debug)]
+pub struct DebugLinks {
+    pub link: Option<DebugLink>,
+    pub altlink: Option<DebugAltLink>,
+    // File was created with `objcopy --only-keep-debug'
+    pub debug_only: bool,
+}
+
+impl DebugLink {
+    fn new(data: &[u8]) -> Result<DebugLink> {
+        let (filename, _) = split_name(data)?;
+
+        // the name is padded to 4 bytes boundary including
+        // the null terminator
+        let offset = (filename.len() + 1 + 3) & !3;
+        let crc = data
+            .get(offset..offset + 4)
+            .ok_or_else(|| ElfError::Malformed("debug link CRC".to_string()))?;
+
+        Ok(DebugLink {
+            filename,
+            crc: u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]),
+        })
+    }
+}
+
+impl DebugLinkStatus {
+    // Look for the companion file in the same order as debuggers do:
+    // next to the binary, in the `.debug' subdirectory and in the
+    // global debug directory (followed by the binary's directory)
+    pub fn new(
+        link: Option<&DebugLink>,
+        binary: &Path,
+        debug_dir: &Path,
+    ) -> Result<DebugLinkStatus> {
+        let link = match link {
+            Some(link) => link,
+            None => return Ok(DebugLinkStatus::NoLink),
+        };
+
+        let binary = binary.canonicalize()?;
+        let parent = binary.parent().unwrap_or_else(|| Path::new("/"));
+        let relative = parent.strip_prefix("/").unwrap_or(parent);
+
+        let candidates = vec![
+            parent.join(&link.filename),
+            parent.join(".debug").join(&link.filename),
+            debug_dir.join(relative).join(&link.filename),
+        ];
+
+        for path in &candidates {
+            // the binary itself may carry the same name as the link
+            if !path.is_file() || *path == binary {
+                continue;
+            }
+
+            let actual = crc32(&std::fs::read(path)?);
+
+            if actual != link.crc {
+                return Ok(DebugLinkStatus::Mismatch {
+                    path: path.clone(),
+                    expected: link.crc,
+                    actual,
+                });
+            }
+
+            return Ok(DebugLinkStatus::Match(path.clone()));
+        }
+
+        Ok(DebugLinkStatus::NotFound(candidates))
+    }
+}
+
+impl BuildIdDebugFile {
+    // `build_id' is in the compact lowercase hex form
+    pub fn new(build_id: Option<String>, debug_dirs: &[PathBuf]) -> BuildIdDebugFile {
+        let candidates = match &build_id {
+            // the first byte names the subdirectory
+            Some(id) if id.len() > 2 => debug_dirs
+                .iter()
+                .map(|dir| {
+                    let path = dir
+                        .join(".build-id")
+                        .join(&id[..2])
+                        .join(format!("{}.debug", &id[2..]));
+                    let size = std::fs::metadata(&path)
+                        .ok()
+                        .filter(|metadata| metadata.is_file())
+                        .map(|metadata| metadata.len());
+
+                    (path, size)
+                })
+                .collect(),
+            _ => vec![],
+        };
+
+        BuildIdDebugFile {
+            build_id,
+            candidates,
+        }
+    }
+}
+
+impl DebugAltLink {
+    fn new(data: &[u8]) -> Result<DebugAltLink> {
+        let (filename, build_id) = split_name(data)?;
+
+        Ok(DebugAltLink {
+            filename,
+            build_id: build_id.to_vec(),
+        })
+    }
+}
+
+impl DebugLinks {
+    pub fn new(headers: &SectionHeaders, reader: &mut Reader) -> Result<DebugLinks> {
+        let link = match headers.get_by_name(".gnu_debuglink") {
+            Some(header) => Some(DebugLink::new(&read_section(&header, reader)?)?),
+            None => None,
+        };
+
+        let altlink = match headers.get_by_name(".gnu_debugaltlink") {
+            Some(header) => Some(DebugAltLink::new(&read_section(&header, reader)?)?),
+            None => None,
+        };
+
+        Ok(DebugLinks {
+            link,
+            altlink,
+            debug_only: is_debug_only(headers),
+        })
+    }
+}
+
+impl fmt::Display for DebugLinks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.debug_only {
+            writeln!(f, "File contains only debug information")?;
+        }
+
+        if self.link.is_none() && self.altlink.is_none() {
+            return writeln!(f, "There are no debug links in this file.");
+        }
+
+        if let Some(link) = &self.link {
+            writeln!(f, "Debug link:")?;
+            writeln!(f, "{:<16}{}", "Filename:", link.filename)?;
+            writeln!(f, "{:<16}{:#010x}", "CRC:", link.crc)?;
+        }
+
+        if let Some(altlink) = &self.altlink {
+            let build_id: Vec<String> = altlink
+                .build_id
+                .iter()
+                .map(|b| format!("{:02x}", b))
+                .collect();
+
+            writeln!(f, "Debug alt link:")?;
+            writeln!(f, "{:<16}{}", "Filename:", altlink.filename)?;
+            writeln!(f, "{:<16}{}", "BuildID:", build_id.join(""))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for BuildIdDebugFile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let build_id = match &self.build_id {
+            Some(build_id) => build_id,
+            None => return writeln!(f, "There is no build ID in this file."),
+        };
+
+        writeln!(f, "{:<16}{}", "BuildID:", build_id)?;
+
+        for (path, size) in &self.candidates {
+            match size {
+                Some(size) => writeln!(f, "  {} ({} bytes)", path.display(), size)?,
+                None => writeln!(f, "  {} (not found)", path.display())?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DebugLinkStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DebugLinkStatus::NoLink => writeln!(f, "There is no debug link in this file."),
+            DebugLinkStatus::NotFound(paths) => {
+                writeln!(f, "Debug file not found, searched:")?;
+
+                for path in paths {
+                    writeln!(f, "  {}", path.display())?;
+                }
+                Ok(())
+            }
+            DebugLinkStatus::Mismatch {
+                path,
+                expected,
+                actual,
+            } => writeln!(
+                f,
+                "Debug file {} CRC mismatch: expected {:#010x}, got {:#010x}",
+                path.display(),
+                expected,
+                actual
+            ),
+            DebugLinkStatus::Match(path) => {
+                writeln!(f, "Debug file {} matches the debug link", path.display())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, ElfBuilder, Section};
+
+    // SHT_PROGBITS, SHT_NOBITS
+    const PROGBITS: u32 = 1;
+    const NOBITS: u32 = 8;
+
+    fn read(sh_type: u32) -> Result<Vec<u8>> {
+        // 1 TiB section, there is nothing of it in the file
+        let mut section = Section::new(".bss", sh_type, vec![]);
+        section.sh_size = Some(1 << 40);
+
+        // EXEC, x86-64
+        let (header, mut reader) = parse(ElfBuilder::new(2, 62).section(section).build());
+        let sections = SectionHeaders::new(&header, &mut reader)?;
+        let header = sections.get_by_name(".bss").unwrap();
+
+        read_section(&header, &mut reader)
+    }
+
+    #[test]
+    fn nobits_section_is_empty() {
+        assert!(read(NOBITS).unwrap().is_empty());
+    }
+
+    #[test]
+    fn section_past_end_of_file() {
+        assert!(read(PROGBITS).unwrap_err().is_eof());
+    }
+}
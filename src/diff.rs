@@ -0,0 +1,245 @@
+use crate::dynamic::DynamicSection;
+use crate::file::ElfFileHeader;
+use crate::section::SectionHeaders;
+use crate::symbols::SymbolTables;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+// Metadata of a single file that takes part in the comparison
+#[derive(Debug)]
+pub struct ElfSummary {
+    entry: u64,
+    build_id: Option<String>,
+    // Size of the section with the index and name, the index keeps
+    // sections with the same name (e.g. several .text in objects) apart
+    sections: BTreeMap<(usize, String), u64>,
+    symbols: BTreeSet<String>,
+    needed: BTreeSet<String>,
+}
+
+#[derive(Debug)]
+enum Change {
+    Added(String),
+    Removed(String),
+    Changed(String, String),
+}
+
+#[derive(Debug)]
+pub struct ElfDiff {
+    entry: Option<(u64, u64)>,
+    build_id: Option<(Option<String>, Option<String>)>,
+    sections: Vec<Change>,
+    symbols: Vec<Change>,
+    needed: Vec<Change>,
+}
+
+impl ElfSummary {
+    pub fn new(
+        header: &ElfFileHeader,
+        sections: &SectionHeaders,
+        symbols: &SymbolTables,
+        dynamic: Option<&DynamicSection>,
+        build_id: Option<String>,
+    ) -> ElfSummary {
+        let mut section_sizes = BTreeMap::new();
+
+        for (index, section) in sections.headers.iter().enumerate() {
+            let name = sections.strtab.get(section.sh_name as u64);
+            section_sizes.insert((index, name), section.sh_size);
+        }
+
+        let needed = match dynamic {
+            Some(dynamic) => dynamic.needed().into_iter().collect(),
+            None => BTreeSet::new(),
+        };
+
+        ElfSummary {
+            entry: header.e_entry,
+            build_id,
+            sections: section_sizes,
+            symbols: symbols
+                .names()
+                .into_iter()
+                .filter(|name| !name.is_empty())
+                .collect(),
+            needed,
+        }
+    }
+}
+
+// Section as shown in the differences, e.g. `[ 1] .text'
+fn section_name((index, name): &(usize, String)) -> String {
+    format!("[{:>2}] {}", index, name)
+}
+
+fn diff_sets(left: &BTreeSet<String>, right: &BTreeSet<String>) -> Vec<Change> {
+    let mut changes = vec![];
+
+    for name in left.difference(right) {
+        changes.push(Change::Removed(name.clone()));
+    }
+
+    for name in right.difference(left) {
+        changes.push(Change::Added(name.clone()));
+    }
+
+    changes
+}
+
+impl ElfDiff {
+    pub fn new(left: &ElfSummary, right: &ElfSummary) -> ElfDiff {
+        let entry = if left.entry != right.entry {
+            Some((left.entry, right.entry))
+        } else {
+            None
+        };
+
+        let build_id = if left.build_id != right.build_id {
+            Some((left.build_id.clone(), right.build_id.clone()))
+        } else {
+            None
+        };
+
+        let mut sections = vec![];
+
+        for (key, size) in &left.sections {
+            match right.sections.get(key) {
+                None => sections.push(Change::Removed(section_name(key))),
+                Some(other) if other != size => sections.push(Change::Changed(
+                    section_name(key),
+                    format!("size {:#x} -> {:#x}", size, other),
+                )),
+                _ => {}
+            }
+        }
+
+        for key in right.sections.keys() {
+            if !left.sections.contains_key(key) {
+                sections.push(Change::Added(section_name(key)));
+            }
+        }
+
+        ElfDiff {
+            entry,
+            build_id,
+            sections,
+            symbols: diff_sets(&left.symbols, &right.symbols),
+            needed: diff_sets(&left.needed, &right.needed),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entry.is_none()
+            && self.build_id.is_none()
+            && self.sections.is_empty()
+            && self.symbols.is_empty()
+            && self.needed.is_empty()
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Added(name) => writeln!(f, "+ {}", name),
+            Change::Removed(name) => writeln!(f, "- {}", name),
+            Change::Changed(name, what) => writeln!(f, "~ {} ({})", name, what),
+        }
+    }
+}
+
+impl fmt::Display for ElfDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "Files are structurally identical");
+        }
+
+        if let Some((left, right)) = self.entry {
+            writeln!(f, "Entry point: {:#x} -> {:#x}", left, right)?;
+        }
+
+        if let Some((left, right)) = &self.build_id {
+            let none = String::from("none");
+
+            writeln!(
+                f,
+                "Build ID: {} -> {}",
+                left.as_ref().unwrap_or(&none),
+                right.as_ref().unwrap_or(&none)
+            )?;
+        }
+
+        let groups = [
+            ("Sections", &self.sections),
+            ("Symbols", &self.symbols),
+            ("Needed libraries", &self.needed),
+        ];
+
+        for (title, changes) in groups.iter() {
+            if changes.is_empty() {
+                continue;
+            }
+
+            writeln!(f, "{}:", title)?;
+
+            for change in changes.iter() {
+                change.fmt(f)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, ElfBuilder, Section};
+
+    // SHT_PROGBITS
+    const PROGBITS: u32 = 1;
+
+    fn summary(size: usize, build_id: Option<&str>) -> ElfSummary {
+        // REL, x86-64, two sections with the same name as with
+        // -ffunction-sections -fno-unique-section-names
+        let data = ElfBuilder::new(1, 62)
+            .section(Section::new(".text", PROGBITS, vec![0; 16]))
+            .section(Section::new(".text", PROGBITS, vec![0; size]))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let symbols = SymbolTables::new(&sections, &header, &mut reader).unwrap();
+
+        ElfSummary::new(
+            &header,
+            &sections,
+            &symbols,
+            None,
+            build_id.map(String::from),
+        )
+    }
+
+    #[test]
+    fn identical() {
+        let diff = ElfDiff::new(&summary(8, Some("abcd")), &summary(8, Some("abcd")));
+
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn duplicate_section_names() {
+        let diff = ElfDiff::new(&summary(8, None), &summary(24, None));
+
+        assert_eq!(
+            diff.to_string(),
+            "Sections:\n~ [ 2] .text (size 0x8 -> 0x18)\n"
+        );
+    }
+
+    #[test]
+    fn build_id() {
+        let diff = ElfDiff::new(&summary(8, Some("abcd")), &summary(8, None));
+
+        assert_eq!(diff.to_string(), "Build ID: abcd -> none\n");
+    }
+}
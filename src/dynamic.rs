@@ -1,9 +1,67 @@
-use crate::reader::{LittleEndian, ReadBytesExt, Reader, Seek, SeekFrom};
+use crate::error::{ElfError, Result};
+use crate::file::{ElfFileHeader, ObjectType};
+use crate::json;
+use crate::program::{ProgramHeaders, SegmentType};
+use crate::reader::{LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
 use crate::section::{SectionHeaderType, SectionHeaders};
-use anyhow::{Result, Context};
 use crate::symbols::StringTable;
 use std::fmt;
 
+// Object is a position independent executable
+const DF_1_PIE: u64 = 0x08000000;
+// Relocations may modify a non-writable segment
+const DF_TEXTREL: u64 = 0x00000004;
+
+// Names of the DT_FLAGS bits
+const DF_FLAGS: [(u64, &str); 5] = [
+    (0x00000001, "ORIGIN"),
+    (0x00000002, "SYMBOLIC"),
+    (DF_TEXTREL, "TEXTREL"),
+    (0x00000008, "BIND_NOW"),
+    (0x00000010, "STATIC_TLS"),
+];
+
+// Names of the DT_FLAGS_1 bits
+const DF_1_FLAGS: [(u64, &str); 27] = [
+    (0x00000001, "NOW"),
+    (0x00000002, "GLOBAL"),
+    (0x00000004, "GROUP"),
+    (0x00000008, "NODELETE"),
+    (0x00000010, "LOADFLTR"),
+    (0x00000020, "INITFIRST"),
+    (0x00000040, "NOOPEN"),
+    (0x00000080, "ORIGIN"),
+    (0x00000100, "DIRECT"),
+    (0x00000400, "INTERPOSE"),
+    (0x00000800, "NODEFLIB"),
+    (0x00001000, "NODUMP"),
+    (0x00002000, "CONFALT"),
+    (0x00004000, "ENDFILTEE"),
+    (0x00008000, "DISPRELDNE"),
+    (0x00010000, "DISPRELPND"),
+    (0x00020000, "NODIRECT"),
+    (0x00040000, "IGNMULDEF"),
+    (0x00080000, "NOKSYMS"),
+    (0x00100000, "NOHDR"),
+    (0x00200000, "EDITED"),
+    (0x00400000, "NORELOC"),
+    (0x00800000, "SYMINTPOSE"),
+    (0x01000000, "GLOBAUDIT"),
+    (0x02000000, "SINGLETON"),
+    (0x04000000, "STUB"),
+    (DF_1_PIE, "PIE"),
+];
+
+fn flag_names(flags: &[(u64, &str)], value: u64) -> String {
+    let names: Vec<&str> = flags
+        .iter()
+        .filter(|(flag, _)| value & flag != 0)
+        .map(|(_, name)| *name)
+        .collect();
+
+    names.join(" ")
+}
+
 #[derive(Debug)]
 struct DynamicEntry {
     // For each object with this type, tag controls the interpretation
@@ -84,12 +142,18 @@ enum DynamicEntryTag {
     PreInitArraySize,
     // Address of SYMTAB_SHNDX section
     SymtabSectionHeadeIndex,
+    // Total size of Relr relocs
+    RelrSize,
+    // Address of Relr relocs
+    Relr,
+    // Size of one Relr reloc
+    RelrEntSize,
     // Versioning entry types
     GnuVerSym,
     GnuRelaCount,
     GnuRelCount,
-    // State flags
-    StateFlags,
+    // State flags (DT_FLAGS_1)
+    Flags1,
     // Address of version definition table
     GnuVerDef,
     // Number of version definitions
@@ -100,15 +164,75 @@ enum DynamicEntryTag {
     GnuVerNeedNum,
     // GNU-style hash table
     GnuHashTable,
+    // Location of PLT entry for TLS descriptor resolver calls
+    TlsDescPlt,
+    // Location of GOT entry used by TLS descriptor resolver PLT entry
+    TlsDescGot,
+    // Start of conflict section
+    GnuConflict,
+    // Library list
+    GnuLibList,
+    // Configuration information
+    Config,
+    // Dependency auditing
+    DepAudit,
+    // Object auditing
+    Audit,
+    // PLT padding
+    PltPad,
+    // Move table
+    MoveTab,
+    // Syminfo table
+    SymInfo,
+    // Prelinking timestamp
+    GnuPrelinked,
+    // Size of conflict section
+    GnuConflictSize,
+    // Size of library list
+    GnuLibListSize,
+    // Checksum of the object
+    Checksum,
+    // Size of PLT padding
+    PltPadSize,
+    // Size of one move table entry
+    MoveEnt,
+    // Size of move table
+    MoveSize,
+    // Feature selection (DTF_*)
+    Feature1,
+    // Flags for DT_* entries, effecting the following DT_* entry
+    PosFlag1,
+    // Size of syminfo table
+    SymInfoSize,
+    // Size of one syminfo entry
+    SymInfoEnt,
+    // Shared object to load before self
+    Auxiliary,
+    // Shared object to get values from
+    Filter,
     Unknown(u64),
 }
 
+// How the file was recognized as position independent executable
+#[derive(Debug)]
+pub enum PieStatus {
+    // DF_1_PIE is set in DT_FLAGS_1
+    Flag,
+    // ET_DYN with PT_INTERP, used by linkers that do not set DF_1_PIE
+    Interp,
+    SharedObject,
+    NotPie,
+}
+
 #[derive(Debug)]
 pub struct DynamicSection {
     // This header is present if object file participates
     // in dynamic linking
     data: Vec<DynamicEntry>,
     strtab: StringTable,
+    // Names of sections referenced by address entries, resolved only
+    // when section headers are available
+    sections: Vec<(u64, String)>,
 }
 
 impl DynamicEntry {
@@ -160,53 +284,288 @@ impl DynamicEntryTag {
             32 => PreInitArray,
             33 => PreInitArraySize,
             34 => SymtabSectionHeadeIndex,
+            35 => RelrSize,
+            36 => Relr,
+            37 => RelrEntSize,
             0x6ffffff0 => GnuVerSym,
             0x6ffffff9 => GnuRelaCount,
             0x6ffffffa => GnuRelCount,
-            0x6ffffffb => StateFlags,
+            0x6ffffffb => Flags1,
             0x6ffffffc => GnuVerDef,
             0x6ffffffd => GnuVerDefNum,
             0x6ffffffe => GnuVerNeed,
             0x6fffffff => GnuVerNeedNum,
             0x6ffffef5 => GnuHashTable,
+            0x6ffffef6 => TlsDescPlt,
+            0x6ffffef7 => TlsDescGot,
+            0x6ffffef8 => GnuConflict,
+            0x6ffffef9 => GnuLibList,
+            0x6ffffefa => Config,
+            0x6ffffefb => DepAudit,
+            0x6ffffefc => Audit,
+            0x6ffffefd => PltPad,
+            0x6ffffefe => MoveTab,
+            0x6ffffeff => SymInfo,
+            0x6ffffdf5 => GnuPrelinked,
+            0x6ffffdf6 => GnuConflictSize,
+            0x6ffffdf7 => GnuLibListSize,
+            0x6ffffdf8 => Checksum,
+            0x6ffffdf9 => PltPadSize,
+            0x6ffffdfa => MoveEnt,
+            0x6ffffdfb => MoveSize,
+            0x6ffffdfc => Feature1,
+            0x6ffffdfd => PosFlag1,
+            0x6ffffdfe => SymInfoSize,
+            0x6ffffdff => SymInfoEnt,
+            0x7ffffffd => Auxiliary,
+            0x7fffffff => Filter,
             _ => Unknown(value),
         }
     }
 }
 
-impl DynamicSection {
-    pub fn new(headers: &SectionHeaders, mut reader: &mut Reader) -> Result<Option<DynamicSection>> {
+// Entries holding virtual address of a section
+fn is_address(tag: &DynamicEntryTag) -> bool {
+    use DynamicEntryTag::*;
+
+    matches!(tag, GnuVerSym | GnuVerDef | GnuVerNeed)
+}
+
+// Read all dyn entries until you get DT_NULL terminator, at most
+// `count` entries are read
+fn read_entries(reader: &mut Reader, count: u64) -> Result<Vec<DynamicEntry>> {
+    let mut entries: Vec<DynamicEntry> = vec![];
+
+    for _ in 0..count {
+        let entry = DynamicEntry::new(reader)?;
+        let null = entry.tag == DynamicEntryTag::Null;
+
+        entries.push(entry);
 
+        if null {
+            return Ok(entries);
+        }
+    }
+
+    Err(ElfError::Malformed(String::from(
+        "dynamic section without DT_NULL terminator",
+    )))
+}
+
+impl DynamicSection {
+    pub fn new(headers: &SectionHeaders, reader: &mut Reader) -> Result<Option<DynamicSection>> {
         if headers.get(SectionHeaderType::Dynamic).is_none() {
             return Ok(None);
         }
 
-        let header = headers.get(SectionHeaderType::Dynamic)
-                            .context("Unable to get dynamic sections")?;
+        let header = headers
+            .get(SectionHeaderType::Dynamic)
+            .ok_or(ElfError::MissingSection(".dynamic"))?;
 
+        reader.check_range(header.sh_offset, header.sh_size)?;
         reader.seek(SeekFrom::Start(header.sh_offset))?;
-        // read all dyn entries and string table address and size
-        let mut entries: Vec<DynamicEntry> = vec![];
-
-        // read entries until you get DT_NULL terminator
-        loop {
-            let entry = DynamicEntry::new(reader)?;
 
-            entries.push(entry);
+        let entries = read_entries(reader, header.sh_size / header.entsize())?;
 
-            if entries.last().unwrap().tag == DynamicEntryTag::Null {
-                break;
-            }
-        }
+        let strtab_header = headers.try_get_by_index(header.sh_link as usize)?;
+        let strtab = StringTable::new(&strtab_header, reader)?;
 
-        let strtab_header = headers.get_by_index(header.sh_link as usize);
-        let strtab = StringTable::new(&strtab_header, &mut reader);
+        let sections = entries
+            .iter()
+            .filter(|entry| is_address(&entry.tag))
+            .filter_map(|entry| {
+                headers
+                    .get_by_address(entry.value)
+                    .map(|section| (entry.value, headers.strtab.get(section.sh_name as u64)))
+            })
+            .collect();
 
         Ok(Some(DynamicSection {
             strtab,
             data: entries,
+            sections,
         }))
     }
+
+    // Read the dynamic segment, this works also for files without section
+    // headers, the string table is located through DT_STRTAB and DT_STRSZ
+    pub fn new_from_segment(
+        prheaders: &ProgramHeaders,
+        reader: &mut Reader,
+    ) -> Result<Option<DynamicSection>> {
+        let header = match prheaders.get_all(SegmentType::Dynamic).pop() {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        reader.check_range(header.p_offset, header.p_filesz)?;
+        reader.seek(SeekFrom::Start(header.p_offset))?;
+
+        // sizeof(Elf64_Dyn)
+        let entries = read_entries(reader, header.p_filesz / 16)?;
+
+        let mut dynamic = DynamicSection {
+            strtab: StringTable::empty(),
+            data: entries,
+            sections: vec![],
+        };
+
+        let offset = dynamic
+            .value(DynamicEntryTag::Strtab)
+            .and_then(|addr| prheaders.vaddr_to_offset(addr));
+
+        if let (Some(offset), Some(size)) = (offset, dynamic.value(DynamicEntryTag::StrtabSize)) {
+            dynamic.strtab = StringTable::read(offset, size, reader)?;
+        }
+
+        Ok(Some(dynamic))
+    }
+
+    fn value(&self, tag: DynamicEntryTag) -> Option<u64> {
+        self.data
+            .iter()
+            .find(|entry| entry.tag == tag)
+            .map(|entry| entry.value)
+    }
+
+    // Address and number of entries of the version definitions
+    pub fn verdef(&self) -> Option<(u64, u64)> {
+        self.value(DynamicEntryTag::GnuVerDef)
+            .zip(self.value(DynamicEntryTag::GnuVerDefNum))
+    }
+
+    // Address and number of entries of the version requirements
+    pub fn verneed(&self) -> Option<(u64, u64)> {
+        self.value(DynamicEntryTag::GnuVerNeed)
+            .zip(self.value(DynamicEntryTag::GnuVerNeedNum))
+    }
+
+    // Address of the version symbol table, the number of entries is
+    // the number of dynamic symbols
+    pub fn versym(&self) -> Option<u64> {
+        self.value(DynamicEntryTag::GnuVerSym)
+    }
+
+    // Number of entries including the DT_NULL terminator
+    pub fn count(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn flags_1(&self) -> Option<u64> {
+        self.value(DynamicEntryTag::Flags1)
+    }
+
+    // String values (DT_NEEDED) are resolved into `name'
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .data
+            .iter()
+            .map(|entry| {
+                let mut fields = vec![
+                    ("tag", json::string(&entry.tag.to_string())),
+                    ("value", json::hex(entry.value)),
+                ];
+
+                if entry.tag == DynamicEntryTag::Needed {
+                    fields.push(("name", json::string(&self.strtab.get(entry.value))));
+                }
+
+                json::object(&fields)
+            })
+            .collect();
+
+        json::array(&entries)
+    }
+
+    // DT_TEXTREL or DF_TEXTREL, the dynamic linker has to make code
+    // segments writable to apply relocations
+    pub fn has_text_relocations(&self) -> bool {
+        self.data.iter().any(|entry| {
+            entry.tag == DynamicEntryTag::TextRel
+                || (entry.tag == DynamicEntryTag::Flags && entry.value & DF_TEXTREL != 0)
+        })
+    }
+
+    // Names of the shared objects the file depends on
+    pub fn needed(&self) -> Vec<String> {
+        self.data
+            .iter()
+            .filter(|entry| entry.tag == DynamicEntryTag::Needed)
+            .map(|entry| self.strtab.get(entry.value))
+            .collect()
+    }
+}
+
+// Dynamic string table, `.dynstr' or, when it cannot be found by name
+// (e.g. files without section headers), the table pointed to by
+// DT_STRTAB and DT_STRSZ of the dynamic segment
+pub fn dynamic_strtab(
+    headers: &SectionHeaders,
+    prheaders: &ProgramHeaders,
+    reader: &mut Reader,
+) -> Result<Option<StringTable>> {
+    if let Some(strtab) = headers.dynstr(reader)? {
+        return Ok(Some(strtab));
+    }
+
+    let dynamic = match DynamicSection::new_from_segment(prheaders, reader)? {
+        Some(dynamic) => dynamic,
+        None => return Ok(None),
+    };
+
+    match dynamic.value(DynamicEntryTag::Strtab) {
+        Some(_) => Ok(Some(dynamic.strtab)),
+        None => Ok(None),
+    }
+}
+
+impl PieStatus {
+    pub fn new(
+        file: &ElfFileHeader,
+        programs: &ProgramHeaders,
+        dynamic: Option<&DynamicSection>,
+    ) -> PieStatus {
+        match file.e_type {
+            ObjectType::SharedObjectFile => {}
+            _ => return PieStatus::NotPie,
+        }
+
+        // PT_INTERP marks a file meant to be run, it is checked before
+        // DT_FLAGS_1 because not all linkers set DF_1_PIE
+        let interp = !programs.get_all(SegmentType::Interp).is_empty();
+
+        // Option::is_some_and needs a newer compiler than the crate does
+        #[allow(clippy::unnecessary_map_or)]
+        let flag = dynamic
+            .and_then(|dynamic| dynamic.flags_1())
+            .map_or(false, |flags| flags & DF_1_PIE != 0);
+
+        match (interp, flag) {
+            (_, true) => PieStatus::Flag,
+            (true, false) => PieStatus::Interp,
+            (false, false) => PieStatus::SharedObject,
+        }
+    }
+}
+
+impl fmt::Display for PieStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PieStatus::Flag => writeln!(f, "PIE: yes (DF_1_PIE)"),
+            PieStatus::Interp => writeln!(f, "PIE: yes (PT_INTERP)"),
+            PieStatus::SharedObject => writeln!(f, "PIE: no (shared object)"),
+            PieStatus::NotPie => writeln!(f, "PIE: no"),
+        }
+    }
+}
+
+impl fmt::Display for DynamicEntryTag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DynamicEntryTag::Unknown(value) => write!(f, "Unknown({:#x})", value),
+            _ => write!(f, "{:?}", self),
+        }
+    }
 }
 
 impl fmt::Display for DynamicSection {
@@ -215,15 +574,124 @@ impl fmt::Display for DynamicSection {
         writeln!(f, "{:<32} Name/Value", "Tag")?;
 
         for entry in &self.data {
-            write!(f, "{:<32} {:<4}", format!("{:?}", entry.tag), entry.value)?;
+            if is_address(&entry.tag) {
+                write!(f, "{:<32} {:#x}", entry.tag.to_string(), entry.value)?;
+
+                let section = self.sections.iter().find(|(addr, _)| *addr == entry.value);
+
+                if let Some((_, name)) = section {
+                    write!(f, " ({})", name)?;
+                }
+            } else {
+                write!(f, "{:<32} {:<4}", entry.tag.to_string(), entry.value)?;
+            }
 
             if entry.tag == DynamicEntryTag::Needed {
                 let name = self.strtab.get(entry.value);
                 write!(f, " ({})", name)?;
             }
 
+            if entry.tag == DynamicEntryTag::Flags {
+                write!(f, " ({})", flag_names(&DF_FLAGS, entry.value))?;
+            }
+
+            if entry.tag == DynamicEntryTag::Flags1 {
+                write!(f, " ({})", flag_names(&DF_1_FLAGS, entry.value))?;
+            }
+
             writeln!(f)?;
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, ElfBuilder, Section, Segment};
+
+    // PT_DYNAMIC, PT_INTERP
+    const DYNAMIC: u32 = 2;
+    const INTERP: u32 = 3;
+    // SHT_STRTAB, SHT_DYNAMIC
+    const STRTAB: u32 = 3;
+    const SHT_DYNAMIC: u32 = 6;
+    const DT_NEEDED: u64 = 1;
+    const DT_FLAGS_1: u64 = 0x6ffffffb;
+
+    fn dynamic(entries: &[(u64, u64)]) -> Vec<u8> {
+        let mut data = vec![];
+
+        for (tag, value) in entries {
+            data.extend(&tag.to_le_bytes());
+            data.extend(&value.to_le_bytes());
+        }
+
+        data
+    }
+
+    fn dynamic_section(entries: &[(u64, u64)]) -> Result<Option<DynamicSection>> {
+        let mut section = Section::new(".dynamic", SHT_DYNAMIC, dynamic(entries));
+        section.sh_link = 2;
+        section.sh_entsize = 16;
+
+        // DYN, x86-64
+        let data = ElfBuilder::new(3, 62)
+            .section(section)
+            .section(Section::new(".dynstr", STRTAB, b"\0libc.so.6\0".to_vec()))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader)?;
+
+        DynamicSection::new(&sections, &mut reader)
+    }
+
+    #[test]
+    fn reads_section() {
+        let dynamic = dynamic_section(&[(DT_NEEDED, 1), (0, 0)]).unwrap().unwrap();
+
+        assert_eq!(dynamic.count(), 2);
+        assert_eq!(dynamic.needed(), vec!["libc.so.6"]);
+    }
+
+    #[test]
+    fn section_without_null() {
+        // the reads stop at the end of the section instead of running
+        // into the string table
+        let err = dynamic_section(&[(DT_NEEDED, 1), (DT_NEEDED, 1)]).unwrap_err();
+
+        assert!(matches!(err, ElfError::Malformed(_)));
+    }
+
+    fn pie_status(flags_1: u64, interp: bool) -> PieStatus {
+        // DYN, x86-64
+        let mut builder = ElfBuilder::new(3, 62).segment(Segment::new(
+            DYNAMIC,
+            dynamic(&[(DT_FLAGS_1, flags_1), (0, 0)]),
+        ));
+
+        if interp {
+            builder = builder.segment(Segment::new(INTERP, b"/lib/ld.so\0".to_vec()));
+        }
+
+        let (header, mut reader) = parse(builder.build());
+        let segments = ProgramHeaders::new(&header, &mut reader).unwrap();
+        let dynamic = DynamicSection::new_from_segment(&segments, &mut reader).unwrap();
+
+        PieStatus::new(&header, &segments, dynamic.as_ref())
+    }
+
+    #[test]
+    fn pie_flag() {
+        assert!(matches!(pie_status(DF_1_PIE, true), PieStatus::Flag));
+        assert!(matches!(pie_status(DF_1_PIE, false), PieStatus::Flag));
+    }
+
+    #[test]
+    fn pie_interp_without_flag() {
+        // DF_1_NOW only, the linker did not set DF_1_PIE
+        assert!(matches!(pie_status(1, true), PieStatus::Interp));
+        assert!(matches!(pie_status(1, false), PieStatus::SharedObject));
+    }
+}
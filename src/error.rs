@@ -0,0 +1,41 @@
+use crate::file;
+use std::io;
+use std::string::FromUtf8Error;
+use thiserror::Error;
+
+// Errors returned by the parsers, unlike anyhow errors these can be
+// matched on by users of the crate
+#[derive(Error, Debug)]
+pub enum ElfError {
+    #[error("{size} bytes at offset {offset:#x} exceed data size {len:#x}")]
+    Truncated { offset: u64, size: u64, len: u64 },
+    #[error(transparent)]
+    Header(#[from] file::Error),
+    #[error("unsupported address size: {0}")]
+    UnsupportedClass(u8),
+    #[error("invalid section index: {0}")]
+    InvalidSectionIndex(usize),
+    #[error("string table offset {0:#x} is out of bounds")]
+    BadStringOffset(u64),
+    #[error("section {0} is missing")]
+    MissingSection(&'static str),
+    #[error("malformed {0}")]
+    Malformed(String),
+    #[error("invalid string: {0}")]
+    InvalidString(#[from] FromUtf8Error),
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+}
+
+impl ElfError {
+    // Data ended before the structure did, as opposed to malformed contents
+    pub fn is_eof(&self) -> bool {
+        match self {
+            ElfError::Truncated { .. } => true,
+            ElfError::Io(err) => err.kind() == io::ErrorKind::UnexpectedEof,
+            _ => false,
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, ElfError>;
@@ -1,11 +1,41 @@
-use crate::reader::{LittleEndian, ReadBytesExt, Reader};
+use crate::json;
+use crate::reader::{LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
+use byteorder::{BigEndian, ByteOrder};
 use std::fmt;
 use std::io::Read;
 use thiserror::Error;
 
 const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
 
-fn show_machine(value: u16) -> &'static str {
+// Size of the ELF64 file header
+const EHDR_SIZE: u64 = 64;
+
+// Offset, size and name of the ELF64 file header fields
+const EHDR_FIELDS: [(usize, usize, &str); 20] = [
+    (0, 4, "EI_MAG (magic)"),
+    (4, 1, "EI_CLASS (class)"),
+    (5, 1, "EI_DATA (data encoding)"),
+    (6, 1, "EI_VERSION (ident version)"),
+    (7, 1, "EI_OSABI (OS/ABI)"),
+    (8, 1, "EI_ABIVERSION (ABI version)"),
+    (9, 7, "EI_PAD (padding)"),
+    (16, 2, "e_type"),
+    (18, 2, "e_machine"),
+    (20, 4, "e_version"),
+    (24, 8, "e_entry"),
+    (32, 8, "e_phoff"),
+    (40, 8, "e_shoff"),
+    (48, 4, "e_flags"),
+    (52, 2, "e_ehsize"),
+    (54, 2, "e_phentsize"),
+    (56, 2, "e_phnum"),
+    (58, 2, "e_shentsize"),
+    (60, 2, "e_shnum"),
+    (62, 2, "e_shstrndx"),
+];
+
+// Name of the architecture for the e_machine value
+pub fn machine_name(value: u16) -> &'static str {
     match value {
         0 => "No machine",
         1 => "AT&T WE 32100",
@@ -191,7 +221,7 @@ fn show_machine(value: u16) -> &'static str {
         _ => "Unknown",
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum FileClass {
     // Invalid class
     None,
@@ -203,7 +233,7 @@ pub enum FileClass {
     Invalid(u8),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Encoding {
     // Invalid data encoding
     None,
@@ -239,6 +269,18 @@ pub enum OsAbi {
     NovellModesto,
     // OpenBSD
     OpenBsd,
+    // OpenVMS
+    OpenVms,
+    // HP Non-Stop Kernel
+    NonStopKernel,
+    // Amiga Research OS
+    Aros,
+    // FenixOS
+    FenixOs,
+    // Nuxi CloudABI
+    CloudAbi,
+    // Stratus Technologies OpenVOS
+    OpenVos,
     // ARM EABI
     ArmEabi,
     // ARM
@@ -319,12 +361,46 @@ pub struct ElfFileHeader {
     pub e_shstrndx: u16,
 }
 
+// Bytes of the file header as stored in the file, independent of
+// the parsed values
+#[derive(Debug)]
+pub struct RawHeader {
+    data: Vec<u8>,
+}
+
+// Single header value printed by the query options
+#[derive(Debug, Clone, Copy)]
+pub enum HeaderQuery {
+    Entry,
+    Machine,
+    Class,
+    Type,
+}
+
+// Problems found in the identification bytes, version fields and the
+// header size, other checks can add their warnings to the same report
+#[derive(Debug)]
+pub struct HeaderCheck {
+    warnings: Vec<String>,
+}
+
+// Header fields that look wrong when read in the declared byte order and
+// in the opposite one, a mislabeled file makes sense only in the other
+#[derive(Debug)]
+pub struct EndiannessReport {
+    encoding: Encoding,
+    little: Vec<&'static str>,
+    big: Vec<&'static str>,
+}
+
 #[derive(Error, Debug)]
 pub enum Error {
-    #[error("Elf magic mismatch: got: {:02X?}, expected: {:02X?}", magic, ELF_MAGIC)]
-    ElfMagicMismatchError {
-        magic: [u8; 4]
-    },
+    #[error(
+        "Elf magic mismatch: got: {:02X?}, expected: {:02X?}",
+        magic,
+        ELF_MAGIC
+    )]
+    ElfMagicMismatchError { magic: [u8; 4] },
 
     #[error(transparent)]
     IOError(#[from] std::io::Error),
@@ -389,6 +465,204 @@ impl ElfFileHeader {
             e_shstrndx,
         })
     }
+
+    pub fn machine_name(&self) -> &'static str {
+        machine_name(self.e_machine)
+    }
+
+    // Addresses and offsets are hex strings, the other fields numbers or
+    // the same names as in the text output
+    pub fn to_json(&self) -> String {
+        json::object(&[
+            ("class", json::string(&self.e_class.to_string())),
+            ("encoding", json::string(&self.e_encoding.to_string())),
+            ("os_abi", json::string(&self.e_os_abi.to_string())),
+            ("abi_version", self.e_os_abi_version.to_string()),
+            ("type", json::string(&format!("{:?}", self.e_type))),
+            ("machine", json::string(self.machine_name())),
+            ("version", json::string(&format!("{:?}", self.e_version))),
+            ("entry", json::hex(self.e_entry)),
+            ("phoff", json::hex(self.e_phoff)),
+            ("shoff", json::hex(self.e_shoff)),
+            ("flags", json::hex(self.e_flags as u64)),
+            ("ehsize", self.e_ehsize.to_string()),
+            ("phentsize", self.e_phentsize.to_string()),
+            ("phnum", self.e_phnum.to_string()),
+            ("shentsize", self.e_shentsize.to_string()),
+            ("shnum", self.e_shnum.to_string()),
+            ("shstrndx", self.e_shstrndx.to_string()),
+        ])
+    }
+
+    // Value of the queried field in a form suitable for scripts, None
+    // when the file does not specify it
+    pub fn query(&self, query: HeaderQuery) -> Option<String> {
+        match query {
+            HeaderQuery::Entry => match self.e_entry {
+                0 => None,
+                entry => Some(format!("{:#x}", entry)),
+            },
+            HeaderQuery::Machine => match self.machine_name() {
+                "No machine" | "Unknown" => None,
+                name => Some(name.to_string()),
+            },
+            HeaderQuery::Class => match self.e_class {
+                FileClass::ElfClass32 => Some("32".to_string()),
+                FileClass::ElfClass64 => Some("64".to_string()),
+                _ => None,
+            },
+            HeaderQuery::Type => match self.e_type {
+                ObjectType::RelocatableFile => Some("REL".to_string()),
+                ObjectType::ExecutableFile => Some("EXEC".to_string()),
+                ObjectType::SharedObjectFile => Some("DYN".to_string()),
+                ObjectType::CoreFile => Some("CORE".to_string()),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl RawHeader {
+    // Read `ehsize` bytes from the start of the file, the whole ELF64
+    // header is used when the size is suspect
+    pub fn new(ehsize: u16, reader: &mut Reader) -> Result<RawHeader, Error> {
+        let size = match ehsize as u64 {
+            16..=EHDR_SIZE => ehsize as u64,
+            _ => EHDR_SIZE,
+        };
+
+        let size = size.min(reader.len());
+        let mut data = vec![0; size as usize];
+
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut data)?;
+
+        Ok(RawHeader { data })
+    }
+}
+
+impl EndiannessReport {
+    pub fn new(header: &ElfFileHeader, reader: &mut Reader) -> Result<EndiannessReport, Error> {
+        let len = reader.len();
+        let mut data = vec![0; EHDR_SIZE as usize];
+
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut data[..len.min(EHDR_SIZE) as usize])?;
+
+        Ok(EndiannessReport {
+            encoding: header.e_encoding,
+            little: suspicious_fields::<LittleEndian>(&data, len),
+            big: suspicious_fields::<BigEndian>(&data, len),
+        })
+    }
+
+    // Declared encoding is valid and the header makes sense in it
+    pub fn is_ok(&self) -> bool {
+        match self.encoding {
+            Encoding::LittleEndian | Encoding::BigEndian => self.declared().0.is_empty(),
+            _ => false,
+        }
+    }
+
+    // Fields suspicious in the declared and in the opposite byte order,
+    // files with an invalid encoding are compared as little endian
+    fn declared(&self) -> (&[&'static str], &[&'static str], &'static str) {
+        match self.encoding {
+            Encoding::BigEndian => (&self.big, &self.little, "little endian"),
+            _ => (&self.little, &self.big, "big endian"),
+        }
+    }
+}
+
+// Names of ELF64 header fields with implausible values when the header is
+// read with the byte order `E'
+fn suspicious_fields<E: ByteOrder>(data: &[u8], len: u64) -> Vec<&'static str> {
+    let e_type = E::read_u16(&data[16..]);
+    let e_version = E::read_u32(&data[20..]);
+    let e_phoff = E::read_u64(&data[32..]);
+    let e_shoff = E::read_u64(&data[40..]);
+    let e_ehsize = E::read_u16(&data[52..]);
+    let e_phentsize = E::read_u16(&data[54..]);
+    let e_phnum = E::read_u16(&data[56..]) as u64;
+    let e_shentsize = E::read_u16(&data[58..]);
+    let e_shnum = E::read_u16(&data[60..]) as u64;
+    let e_shstrndx = E::read_u16(&data[62..]) as u64;
+
+    let checks = [
+        // types up to ET_CORE and the OS and processor specific ranges
+        (e_type <= 4 || e_type >= 0xfe00, "e_type"),
+        (e_version == 1, "e_version"),
+        (e_phoff <= len, "e_phoff"),
+        (e_shoff <= len, "e_shoff"),
+        (e_ehsize as u64 == EHDR_SIZE, "e_ehsize"),
+        (e_phentsize == 0 || e_phentsize == 56, "e_phentsize"),
+        (e_phnum * 56 <= len, "e_phnum"),
+        (e_shentsize == 0 || e_shentsize == 64, "e_shentsize"),
+        (e_shnum * 64 <= len, "e_shnum"),
+        // SHN_XINDEX moves the index to the first section header
+        (
+            e_shstrndx < e_shnum.max(1) || e_shstrndx == 0xffff,
+            "e_shstrndx",
+        ),
+    ];
+
+    checks
+        .iter()
+        .filter(|(plausible, _)| !plausible)
+        .map(|(_, name)| *name)
+        .collect()
+}
+
+impl HeaderCheck {
+    pub fn new(header: &ElfFileHeader) -> HeaderCheck {
+        let mut warnings = vec![];
+
+        for (i, byte) in header.e_padding_.iter().enumerate() {
+            if *byte != 0 {
+                warnings.push(format!(
+                    "Padding byte {} (e_ident[{}]) is not zero: {:#04x}",
+                    i,
+                    i + 9,
+                    byte
+                ));
+            }
+        }
+
+        if header.e_version_ != 1 {
+            warnings.push(format!(
+                "Identification version (e_ident[6]) is not EV_CURRENT: {:#04x}",
+                header.e_version_
+            ));
+        }
+
+        match header.e_version {
+            Version::Current => {}
+            Version::Unspecified => {
+                warnings.push("File version (e_version) is not EV_CURRENT: 0x0".to_string())
+            }
+            Version::Invalid(value) => warnings.push(format!(
+                "File version (e_version) is not EV_CURRENT: {:#x}",
+                value
+            )),
+        }
+
+        if header.e_ehsize as u64 != EHDR_SIZE {
+            warnings.push(format!(
+                "File header size (e_ehsize) is not {}: {}",
+                EHDR_SIZE, header.e_ehsize
+            ));
+        }
+
+        HeaderCheck { warnings }
+    }
+
+    pub fn extend(&mut self, warnings: Vec<String>) {
+        self.warnings.extend(warnings);
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.warnings.is_empty()
+    }
 }
 
 impl FileClass {
@@ -429,6 +703,12 @@ impl OsAbi {
             10 => CompaqTru64Unix,
             11 => NovellModesto,
             12 => OpenBsd,
+            13 => OpenVms,
+            14 => NonStopKernel,
+            15 => Aros,
+            16 => FenixOs,
+            17 => CloudAbi,
+            18 => OpenVos,
             64 => ArmEabi,
             97 => Arm,
             255 => Standalone,
@@ -462,18 +742,148 @@ impl Version {
     }
 }
 
+impl fmt::Display for RawHeader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Raw Elf Header ({} bytes):", self.data.len())?;
+
+        for (offset, size, name) in EHDR_FIELDS.iter() {
+            let bytes = match self.data.get(*offset..offset + size) {
+                Some(bytes) => bytes,
+                None => break,
+            };
+
+            let hex: Vec<String> = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+            writeln!(f, "{:#04x}  {:<24} {}", offset, hex.join(" "), name)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for EndiannessReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let fields = |names: &[&str]| match names.len() {
+            0 => "none".to_string(),
+            _ => names.join(", "),
+        };
+
+        writeln!(f, "Endianness report:")?;
+        writeln!(f, "{:<32}{}", "Declared encoding:", self.encoding)?;
+        writeln!(
+            f,
+            "{:<32}{}",
+            "Suspicious as little endian:",
+            fields(&self.little)
+        )?;
+        writeln!(
+            f,
+            "{:<32}{}",
+            "Suspicious as big endian:",
+            fields(&self.big)
+        )?;
+
+        let (declared, opposite, other) = self.declared();
+
+        match self.encoding {
+            Encoding::LittleEndian | Encoding::BigEndian => {}
+            _ => writeln!(f, "Warning: declared encoding is invalid")?,
+        }
+
+        if opposite.len() < declared.len() {
+            writeln!(
+                f,
+                "Possible endianness mismatch, the header makes sense as {}",
+                other
+            )
+        } else if declared.is_empty() {
+            writeln!(f, "Encoding looks correct")
+        } else {
+            writeln!(
+                f,
+                "Encoding looks correct, but the header is suspicious in both byte orders"
+            )
+        }
+    }
+}
+
+impl fmt::Display for HeaderCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_ok() {
+            return writeln!(f, "Header check passed");
+        }
+
+        for warning in &self.warnings {
+            writeln!(f, "Warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for FileClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FileClass::None => write!(f, "none"),
+            FileClass::ElfClass32 => write!(f, "ELF32"),
+            FileClass::ElfClass64 => write!(f, "ELF64"),
+            FileClass::Invalid(value) => write!(f, "<unknown: {:x}>", value),
+        }
+    }
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Encoding::None => write!(f, "none"),
+            Encoding::LittleEndian => write!(f, "2's complement, little endian"),
+            Encoding::BigEndian => write!(f, "2's complement, big endian"),
+            Encoding::Invalid(value) => write!(f, "<unknown: {:x}>", value),
+        }
+    }
+}
+
+impl fmt::Display for OsAbi {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use OsAbi::*;
+
+        match self {
+            UnixVSystem => write!(f, "UNIX - System V"),
+            HpUx => write!(f, "UNIX - HP-UX"),
+            NetBsd => write!(f, "UNIX - NetBSD"),
+            GnuElfExtensions => write!(f, "UNIX - GNU"),
+            SunSolaris => write!(f, "UNIX - Solaris"),
+            IbmAix => write!(f, "UNIX - AIX"),
+            SgiIrix => write!(f, "UNIX - IRIX"),
+            FreeBsd => write!(f, "UNIX - FreeBSD"),
+            CompaqTru64Unix => write!(f, "UNIX - TRU64"),
+            NovellModesto => write!(f, "Novell - Modesto"),
+            OpenBsd => write!(f, "UNIX - OpenBSD"),
+            OpenVms => write!(f, "VMS - OpenVMS"),
+            NonStopKernel => write!(f, "HP - Non-Stop Kernel"),
+            Aros => write!(f, "AROS"),
+            FenixOs => write!(f, "FenixOS"),
+            CloudAbi => write!(f, "Nuxi CloudABI"),
+            OpenVos => write!(f, "Stratus Technologies OpenVOS"),
+            ArmEabi => write!(f, "ARM EABI"),
+            Arm => write!(f, "ARM"),
+            Standalone => write!(f, "Standalone App"),
+            Invalid(value) => write!(f, "Unknown OS ABI ({})", value),
+        }
+    }
+}
+
 impl fmt::Display for ElfFileHeader {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "Elf Header:")?;
 
         writeln!(f, "{:<32}{:x?}", "Magic:", self.e_magic)?;
-        writeln!(f, "{:<32}{:?}", "Class:", self.e_class)?;
-        writeln!(f, "{:<32}{:?}", "Encoding:", self.e_encoding)?;
-        writeln!(f, "{:<32}{:?}", "OS/ABI:", self.e_os_abi)?;
+        writeln!(f, "{:<32}{}", "Class:", self.e_class)?;
+        writeln!(f, "{:<32}{}", "Encoding:", self.e_encoding)?;
+        writeln!(f, "{:<32}{}", "OS/ABI:", self.e_os_abi)?;
         writeln!(f, "{:<32}{}", "ABI Version:", self.e_os_abi_version)?;
         writeln!(f, "{:<32}{:x?}", "Padding:", self.e_padding_)?;
         writeln!(f, "{:<32}{:?}", "Type:", self.e_type)?;
-        writeln!(f, "{:<32}{}", "Architecture:", show_machine(self.e_machine))?;
+        writeln!(f, "{:<32}{}", "Architecture:", self.machine_name())?;
         writeln!(f, "{:<32}{:?}", "Version:", self.e_version)?;
         writeln!(f, "{:<32}{:#x}", "Entry point address:", self.e_entry)?;
         writeln!(f, "{:<32}{}", "Program header offset:", self.e_phoff)?;
@@ -491,3 +901,138 @@ impl fmt::Display for ElfFileHeader {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, ElfBuilder};
+
+    // EXEC, x86-64
+    fn executable() -> Vec<u8> {
+        ElfBuilder::new(2, 62).build()
+    }
+
+    fn row(offset: usize, hex: &str, name: &str) -> String {
+        format!("{:#04x}  {:<24} {}\n", offset, hex, name)
+    }
+
+    #[test]
+    fn raw_header_fields() {
+        // the fields follow each other up to the end of the header
+        let mut end = 0;
+        for (offset, size, _) in EHDR_FIELDS.iter() {
+            assert_eq!(*offset, end);
+            end += size;
+        }
+        assert_eq!(end as u64, EHDR_SIZE);
+
+        let (header, mut reader) = parse(executable());
+        let raw = RawHeader::new(header.e_ehsize, &mut reader)
+            .unwrap()
+            .to_string();
+
+        assert!(raw.starts_with("Raw Elf Header (64 bytes):\n"), "{}", raw);
+        assert!(raw.contains(&row(4, "02", "EI_CLASS (class)")), "{}", raw);
+        assert!(raw.contains(&row(18, "3e 00", "e_machine")), "{}", raw);
+        assert!(raw.ends_with(&row(62, "01 00", "e_shstrndx")), "{}", raw);
+    }
+
+    #[test]
+    fn raw_header_size() {
+        let (_, mut reader) = parse(executable());
+
+        // the dump stops after the last whole field
+        let raw = RawHeader::new(24, &mut reader).unwrap().to_string();
+        assert!(
+            raw.ends_with(&row(20, "01 00 00 00", "e_version")),
+            "{}",
+            raw
+        );
+
+        // a suspect size shows the whole header
+        let raw = RawHeader::new(0x1000, &mut reader).unwrap().to_string();
+        assert!(raw.starts_with("Raw Elf Header (64 bytes):\n"), "{}", raw);
+    }
+
+    fn check(data: Vec<u8>) -> String {
+        let (header, _) = parse(data);
+
+        HeaderCheck::new(&header).to_string()
+    }
+
+    #[test]
+    fn header_check_passes() {
+        assert_eq!(check(executable()), "Header check passed\n");
+    }
+
+    #[test]
+    fn header_check_warnings() {
+        let mut data = executable();
+        // the third padding byte and e_ehsize of ELF32
+        data[11] = 0xab;
+        data[52..54].copy_from_slice(&52u16.to_le_bytes());
+
+        assert_eq!(
+            check(data),
+            "Warning: Padding byte 2 (e_ident[11]) is not zero: 0xab\n\
+             Warning: File header size (e_ehsize) is not 64: 52\n"
+        );
+    }
+
+    #[test]
+    fn queries() {
+        let mut data = executable();
+        data[24..32].copy_from_slice(&0x401000u64.to_le_bytes());
+        let (header, _) = parse(data);
+
+        assert_eq!(
+            header.query(HeaderQuery::Entry),
+            Some("0x401000".to_string())
+        );
+        assert_eq!(
+            header.query(HeaderQuery::Machine),
+            Some("AMD x86-64 architecture".to_string())
+        );
+        assert_eq!(header.query(HeaderQuery::Class), Some("64".to_string()));
+        assert_eq!(header.query(HeaderQuery::Type), Some("EXEC".to_string()));
+    }
+
+    #[test]
+    fn query_without_entry() {
+        let (header, _) = parse(executable());
+
+        assert_eq!(header.query(HeaderQuery::Entry), None);
+    }
+
+    fn endianness(data: Vec<u8>) -> EndiannessReport {
+        let (header, mut reader) = parse(data);
+
+        EndiannessReport::new(&header, &mut reader).unwrap()
+    }
+
+    #[test]
+    fn native_endianness() {
+        let report = endianness(executable());
+
+        assert!(report.is_ok());
+        assert!(report.little.is_empty());
+        assert!(report.to_string().ends_with("Encoding looks correct\n"));
+    }
+
+    #[test]
+    fn swapped_endianness() {
+        // fields after e_ident in big endian, still declared as little
+        let mut data = executable();
+        for (offset, size, _) in EHDR_FIELDS.iter().filter(|field| field.0 >= 16) {
+            data[*offset..offset + size].reverse();
+        }
+
+        let report = endianness(data);
+
+        assert!(!report.is_ok());
+        assert!(report.big.is_empty());
+        assert!(report
+            .to_string()
+            .ends_with("Possible endianness mismatch, the header makes sense as big endian\n"));
+    }
+}
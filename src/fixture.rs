@@ -0,0 +1,209 @@
+// Small ELF64 little endian files built in memory for the unit tests,
+// the layout is: file header, program headers, segment data, section
+// data, section names and section headers
+use crate::file::ElfFileHeader;
+use crate::reader::Reader;
+
+const EHDR_SIZE: usize = 64;
+const PHDR_SIZE: usize = 56;
+const SHDR_SIZE: usize = 64;
+
+pub struct Section {
+    pub name: &'static str,
+    pub sh_type: u32,
+    pub sh_flags: u64,
+    pub sh_addr: u64,
+    pub sh_link: u32,
+    pub sh_info: u32,
+    pub sh_entsize: u64,
+    // Size in the header when it differs from the data length
+    pub sh_size: Option<u64>,
+    pub data: Vec<u8>,
+}
+
+// Segment with its own data, it does not overlap any section
+pub struct Segment {
+    pub p_type: u32,
+    pub p_flags: u32,
+    pub p_vaddr: u64,
+    // Size in the header when it differs from the data length
+    pub p_filesz: Option<u64>,
+    pub data: Vec<u8>,
+}
+
+pub struct ElfBuilder {
+    e_type: u16,
+    e_machine: u16,
+    sections: Vec<Section>,
+    segments: Vec<Segment>,
+}
+
+impl Section {
+    pub fn new(name: &'static str, sh_type: u32, data: Vec<u8>) -> Section {
+        Section {
+            name,
+            sh_type,
+            sh_flags: 0,
+            sh_addr: 0,
+            sh_link: 0,
+            sh_info: 0,
+            sh_entsize: 0,
+            sh_size: None,
+            data,
+        }
+    }
+}
+
+impl Segment {
+    pub fn new(p_type: u32, data: Vec<u8>) -> Segment {
+        Segment {
+            p_type,
+            p_flags: 0,
+            p_vaddr: 0,
+            p_filesz: None,
+            data,
+        }
+    }
+}
+
+fn align(data: &mut Vec<u8>) {
+    data.resize((data.len() + 7) & !7, 0);
+}
+
+impl ElfBuilder {
+    pub fn new(e_type: u16, e_machine: u16) -> ElfBuilder {
+        ElfBuilder {
+            e_type,
+            e_machine,
+            sections: vec![],
+            segments: vec![],
+        }
+    }
+
+    // Sections get indices from 1 in the order they are added, the null
+    // section and `.shstrtab' are added by `build'
+    pub fn section(mut self, section: Section) -> ElfBuilder {
+        self.sections.push(section);
+        self
+    }
+
+    pub fn segment(mut self, segment: Segment) -> ElfBuilder {
+        self.segments.push(segment);
+        self
+    }
+
+    pub fn build(self) -> Vec<u8> {
+        let phnum = self.segments.len();
+        let mut data = vec![0; EHDR_SIZE + phnum * PHDR_SIZE];
+        let mut names = vec![0];
+        let mut shdrs = vec![0; SHDR_SIZE];
+
+        let shstrtab = Section::new(".shstrtab", 3, vec![]);
+        let count = self.sections.len() + 2;
+
+        let mut phdrs = vec![];
+
+        for segment in &self.segments {
+            let offset = data.len() as u64;
+            let size = segment.data.len() as u64;
+
+            data.extend(&segment.data);
+            align(&mut data);
+
+            phdrs.extend(&segment.p_type.to_le_bytes());
+            phdrs.extend(&segment.p_flags.to_le_bytes());
+            phdrs.extend(&offset.to_le_bytes());
+            phdrs.extend(&segment.p_vaddr.to_le_bytes());
+            phdrs.extend(&segment.p_vaddr.to_le_bytes());
+            phdrs.extend(&segment.p_filesz.unwrap_or(size).to_le_bytes());
+            phdrs.extend(&size.to_le_bytes());
+            phdrs.extend(&8u64.to_le_bytes());
+        }
+
+        data[EHDR_SIZE..EHDR_SIZE + phdrs.len()].copy_from_slice(&phdrs);
+
+        for section in self.sections.iter().chain(std::iter::once(&shstrtab)) {
+            let name = names.len() as u32;
+            names.extend(section.name.as_bytes());
+            names.push(0);
+
+            let contents = match section.name {
+                ".shstrtab" => &names,
+                _ => &section.data,
+            };
+            let offset = data.len() as u64;
+            let size = section.sh_size.unwrap_or(contents.len() as u64);
+
+            data.extend(contents);
+            align(&mut data);
+
+            shdrs.extend(&name.to_le_bytes());
+            shdrs.extend(&section.sh_type.to_le_bytes());
+            shdrs.extend(&section.sh_flags.to_le_bytes());
+            shdrs.extend(&section.sh_addr.to_le_bytes());
+            shdrs.extend(&offset.to_le_bytes());
+            shdrs.extend(&size.to_le_bytes());
+            shdrs.extend(&section.sh_link.to_le_bytes());
+            shdrs.extend(&section.sh_info.to_le_bytes());
+            shdrs.extend(&8u64.to_le_bytes());
+            shdrs.extend(&section.sh_entsize.to_le_bytes());
+        }
+
+        let shoff = data.len() as u64;
+        data.extend(&shdrs);
+
+        let header = &mut data[..EHDR_SIZE];
+
+        header[..4].copy_from_slice(b"\x7fELF");
+        // ELFCLASS64, ELFDATA2LSB, EV_CURRENT
+        header[4..7].copy_from_slice(&[2, 1, 1]);
+        header[16..18].copy_from_slice(&self.e_type.to_le_bytes());
+        header[18..20].copy_from_slice(&self.e_machine.to_le_bytes());
+        header[20..24].copy_from_slice(&1u32.to_le_bytes());
+        if phnum > 0 {
+            header[32..40].copy_from_slice(&(EHDR_SIZE as u64).to_le_bytes());
+        }
+        header[40..48].copy_from_slice(&shoff.to_le_bytes());
+        header[52..54].copy_from_slice(&(EHDR_SIZE as u16).to_le_bytes());
+        header[54..56].copy_from_slice(&(PHDR_SIZE as u16).to_le_bytes());
+        header[56..58].copy_from_slice(&(phnum as u16).to_le_bytes());
+        header[58..60].copy_from_slice(&(SHDR_SIZE as u16).to_le_bytes());
+        header[60..62].copy_from_slice(&(count as u16).to_le_bytes());
+        header[62..64].copy_from_slice(&((count - 1) as u16).to_le_bytes());
+
+        data
+    }
+}
+
+// File header and reader over the data, the reader is left after the
+// file header
+pub fn parse(data: Vec<u8>) -> (ElfFileHeader, Reader) {
+    let mut reader = Reader::new(data);
+    let header = ElfFileHeader::new(&mut reader).unwrap();
+
+    (header, reader)
+}
+
+// ELF64 symbol table entry
+pub fn symbol(name: u32, info: u8, shndx: u16, value: u64, size: u64) -> Vec<u8> {
+    let mut data = vec![];
+
+    data.extend(&name.to_le_bytes());
+    data.push(info);
+    data.push(0);
+    data.extend(&shndx.to_le_bytes());
+    data.extend(&value.to_le_bytes());
+    data.extend(&size.to_le_bytes());
+
+    data
+}
+
+// Remove the section headers from the file header, the section data stay
+// in the file as with `strip --strip-section-headers'
+pub fn strip(mut data: Vec<u8>) -> Vec<u8> {
+    // e_shoff, e_shnum and e_shstrndx
+    data[40..48].copy_from_slice(&[0; 8]);
+    data[60..64].copy_from_slice(&[0; 4]);
+
+    data
+}
@@ -0,0 +1,281 @@
+use crate::debuglink::read_section;
+use crate::error::{ElfError, Result};
+use crate::program::ProgramHeaders;
+use crate::reader::{Cursor, LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
+use crate::section::SectionHeaders;
+use byteorder::BigEndian;
+use std::fmt;
+use std::io::Read;
+
+// Magic at the start of the `.go.buildinfo' section
+const BUILDINFO_MAGIC: &[u8] = b"\xff Go buildinf:";
+
+/* Layout of the build info header:
+ *    magic[14]
+ *    uint8 ptrsize
+ *    uint8 flags
+ *    pointers to version and module info (before Go 1.18)
+ *    padding up to 32 bytes
+ */
+const BUILDINFO_HEADER_SIZE: usize = 32;
+const BUILDINFO_POINTERS_OFFSET: u64 = 16;
+
+// Pointers are big endian
+const BUILDINFO_FLAG_BIG_ENDIAN: u8 = 1 << 0;
+// Strings follow the header as varint length and bytes (Go 1.18+)
+const BUILDINFO_FLAG_INLINE: u8 = 1 << 1;
+
+// Module info is wrapped in binary sentinels of this size
+const MODINFO_SENTINEL_SIZE: usize = 16;
+
+// Go toolchain version and modules the binary was built from
+#[derive(Debug)]
+pub struct GoBuildInfo {
+    version: String,
+    // Import path of the main package
+    path: Option<String>,
+    // Path and version of the main module
+    module: Option<(String, String)>,
+    // Number of dependency modules
+    deps: usize,
+}
+
+fn read_pointer(reader: &mut Reader, ptrsize: u8, big_endian: bool) -> Result<u64> {
+    match (ptrsize, big_endian) {
+        (4, false) => Ok(reader.read_u32::<LittleEndian>()? as u64),
+        (4, true) => Ok(reader.read_u32::<BigEndian>()? as u64),
+        (8, false) => Ok(reader.read_u64::<LittleEndian>()?),
+        (8, true) => Ok(reader.read_u64::<BigEndian>()?),
+        _ => Err(ElfError::UnsupportedClass(ptrsize)),
+    }
+}
+
+fn read_bytes(reader: &mut Reader, offset: u64, size: u64) -> Result<Vec<u8>> {
+    reader.check_range(offset, size)?;
+    reader.seek(SeekFrom::Start(offset))?;
+
+    let mut data = vec![0; size as usize];
+    reader.read_exact(&mut data)?;
+
+    Ok(data)
+}
+
+// Read Go string header (data pointer and length) at virtual address
+// `addr` and the string it points to
+fn read_go_string(
+    addr: u64,
+    ptrsize: u8,
+    big_endian: bool,
+    programs: &ProgramHeaders,
+    reader: &mut Reader,
+) -> Result<Vec<u8>> {
+    let translate = |addr: u64| {
+        programs
+            .vaddr_to_offset(addr)
+            .ok_or_else(|| ElfError::Malformed(format!("Go build info pointer {:#x}", addr)))
+    };
+
+    reader.seek(SeekFrom::Start(translate(addr)?))?;
+
+    let data = read_pointer(reader, ptrsize, big_endian)?;
+    let size = read_pointer(reader, ptrsize, big_endian)?;
+
+    if size == 0 {
+        return Ok(vec![]);
+    }
+
+    read_bytes(reader, translate(data)?, size)
+}
+
+// Read string stored as its varint encoded length followed by the bytes
+fn read_varint_string(reader: &mut Reader) -> Result<Vec<u8>> {
+    let size = reader.read_uleb128()?;
+    let offset = reader.position();
+
+    read_bytes(reader, offset, size)
+}
+
+impl GoBuildInfo {
+    pub fn new(
+        headers: &SectionHeaders,
+        programs: &ProgramHeaders,
+        reader: &mut Reader,
+    ) -> Result<Option<GoBuildInfo>> {
+        let header = match headers.get_by_name(".go.buildinfo") {
+            Some(header) => header,
+            None => return Ok(None),
+        };
+
+        let data = read_section(&header, reader)?;
+
+        if data.len() < BUILDINFO_HEADER_SIZE || !data.starts_with(BUILDINFO_MAGIC) {
+            return Err(ElfError::Malformed(String::from("Go build info header")));
+        }
+
+        let ptrsize = data[BUILDINFO_MAGIC.len()];
+        let flags = data[BUILDINFO_MAGIC.len() + 1];
+
+        let (version, modinfo) = if flags & BUILDINFO_FLAG_INLINE != 0 {
+            let mut section = Cursor::new(data);
+            section.set_position(BUILDINFO_HEADER_SIZE as u64);
+
+            let version = read_varint_string(&mut section)?;
+            (version, read_varint_string(&mut section)?)
+        } else {
+            let big_endian = flags & BUILDINFO_FLAG_BIG_ENDIAN != 0;
+
+            let mut section = Cursor::new(data);
+            section.set_position(BUILDINFO_POINTERS_OFFSET);
+
+            let version = read_pointer(&mut section, ptrsize, big_endian)?;
+            let modinfo = read_pointer(&mut section, ptrsize, big_endian)?;
+
+            (
+                read_go_string(version, ptrsize, big_endian, programs, reader)?,
+                read_go_string(modinfo, ptrsize, big_endian, programs, reader)?,
+            )
+        };
+
+        // the sentinels are present only when the module info is
+        let size = modinfo.len();
+        let modinfo = if size > 2 * MODINFO_SENTINEL_SIZE
+            && modinfo[size - MODINFO_SENTINEL_SIZE - 1] == b'\n'
+        {
+            &modinfo[MODINFO_SENTINEL_SIZE..size - MODINFO_SENTINEL_SIZE]
+        } else {
+            &modinfo[..]
+        };
+
+        let mut path = None;
+        let mut module = None;
+        let mut deps = 0;
+
+        for line in String::from_utf8_lossy(modinfo).lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+
+            match fields.as_slice() {
+                ["path", value, ..] => path = Some(value.to_string()),
+                ["mod", name, version, ..] => {
+                    module = Some((name.to_string(), version.to_string()))
+                }
+                ["dep", ..] => deps += 1,
+                _ => {}
+            }
+        }
+
+        Ok(Some(GoBuildInfo {
+            version: String::from_utf8_lossy(&version).into_owned(),
+            path,
+            module,
+            deps,
+        }))
+    }
+}
+
+impl fmt::Display for GoBuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<16}{}", "Go version:", self.version)?;
+
+        if let Some(path) = &self.path {
+            writeln!(f, "{:<16}{}", "Path:", path)?;
+        }
+
+        if let Some((name, version)) = &self.module {
+            writeln!(f, "{:<16}{} {}", "Main module:", name, version)?;
+        }
+
+        writeln!(f, "{:<16}{}", "Dependencies:", self.deps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, ElfBuilder, Section, Segment};
+
+    // SHT_PROGBITS, PT_LOAD
+    const PROGBITS: u32 = 1;
+    const LOAD: u32 = 1;
+
+    const MODINFO: &[u8] = b"path\texample.com/hello\n\
+        mod\texample.com/hello\t(devel)\t\n\
+        dep\tgolang.org/x/text\tv0.3.0\th1:abc=\n";
+
+    // Start of the header with 8 byte pointers, the pointers or the
+    // padding up to the strings are added by the tests
+    fn header(flags: u8) -> Vec<u8> {
+        let mut data = BUILDINFO_MAGIC.to_vec();
+        data.extend(&[8, flags]);
+        data
+    }
+
+    fn build_info(section: Vec<u8>, segment: Option<Segment>) -> GoBuildInfo {
+        // EXEC, x86-64
+        let mut builder = ElfBuilder::new(2, 62);
+        if let Some(segment) = segment {
+            builder = builder.segment(segment);
+        }
+        let data = builder
+            .section(Section::new(".go.buildinfo", PROGBITS, section))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let programs = ProgramHeaders::new(&header, &mut reader).unwrap();
+
+        GoBuildInfo::new(&sections, &programs, &mut reader)
+            .unwrap()
+            .unwrap()
+    }
+
+    fn check(info: &GoBuildInfo, version: &str) {
+        assert_eq!(info.version, version);
+        assert_eq!(info.path.as_deref(), Some("example.com/hello"));
+        assert_eq!(
+            info.module,
+            Some(("example.com/hello".to_string(), "(devel)".to_string()))
+        );
+        assert_eq!(info.deps, 1);
+    }
+
+    #[test]
+    fn inline_strings() {
+        // the module info is wrapped in the sentinels
+        let mut modinfo = vec![0xaa; MODINFO_SENTINEL_SIZE];
+        modinfo.extend(MODINFO);
+        modinfo.extend(vec![0xbb; MODINFO_SENTINEL_SIZE]);
+
+        let mut data = header(BUILDINFO_FLAG_INLINE);
+        data.resize(BUILDINFO_HEADER_SIZE, 0);
+        data.push(8);
+        data.extend(b"go1.21.0");
+        data.push(modinfo.len() as u8);
+        data.extend(modinfo);
+
+        check(&build_info(data, None), "go1.21.0");
+    }
+
+    #[test]
+    fn legacy_pointers() {
+        // Go string headers at 0x1000 and 0x1010, the strings follow
+        // them, the module info has no sentinels
+        let mut load = vec![];
+        for (addr, size) in &[(0x1020u64, 6u64), (0x1030, MODINFO.len() as u64)] {
+            load.extend(&addr.to_le_bytes());
+            load.extend(&size.to_le_bytes());
+        }
+        load.extend(b"go1.16");
+        load.resize(0x30, 0);
+        load.extend(MODINFO);
+
+        let mut segment = Segment::new(LOAD, load);
+        segment.p_vaddr = 0x1000;
+
+        let mut data = header(0);
+        data.resize(BUILDINFO_POINTERS_OFFSET as usize, 0);
+        data.extend(&0x1000u64.to_le_bytes());
+        data.extend(&0x1010u64.to_le_bytes());
+
+        check(&build_info(data, Some(segment)), "go1.16");
+    }
+}
@@ -0,0 +1,221 @@
+use crate::error::{ElfError, Result};
+use crate::reader::{LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
+use crate::section::{SectionHeader, SectionHeaderType, SectionHeaders};
+use std::fmt;
+
+// Chain lengths of the buckets of a symbol hash table (.hash or .gnu.hash)
+#[derive(Debug)]
+struct HashTable {
+    name: String,
+    chains: Vec<usize>,
+}
+
+#[derive(Debug)]
+pub struct HashTables {
+    data: Vec<HashTable>,
+}
+
+fn read_words(count: u64, reader: &mut Reader) -> Result<Vec<u32>> {
+    let mut words = vec![];
+
+    for _ in 0..count {
+        words.push(reader.read_u32::<LittleEndian>()?);
+    }
+
+    Ok(words)
+}
+
+impl HashTable {
+    // SysV hash table: nbucket, nchain, bucket[nbucket], chain[nchain],
+    // chain of each bucket ends with STN_UNDEF
+    fn new_sysv(header: &SectionHeader, reader: &mut Reader) -> Result<Vec<usize>> {
+        reader.check_range(header.sh_offset, header.sh_size)?;
+        reader.seek(SeekFrom::Start(header.sh_offset))?;
+
+        let nbucket = reader.read_u32::<LittleEndian>()? as u64;
+        let nchain = reader.read_u32::<LittleEndian>()? as u64;
+
+        if 8 + (nbucket + nchain) * 4 > header.sh_size {
+            return Err(ElfError::Malformed(String::from("hash table size")));
+        }
+
+        let buckets = read_words(nbucket, reader)?;
+        let chain = read_words(nchain, reader)?;
+
+        let mut chains = vec![];
+
+        for bucket in buckets {
+            let mut length = 0;
+            let mut index = bucket as usize;
+
+            // the length check stops loops in corrupted tables
+            while index != 0 && index < chain.len() && length < chain.len() {
+                length += 1;
+                index = chain[index] as usize;
+            }
+
+            chains.push(length);
+        }
+
+        Ok(chains)
+    }
+
+    // GNU hash table: nbuckets, symoffset, bloom_size, bloom_shift,
+    // bloom[bloom_size], buckets[nbuckets], chain[], the lowest bit
+    // of a chain value marks the end of the chain
+    fn new_gnu(header: &SectionHeader, reader: &mut Reader) -> Result<Vec<usize>> {
+        reader.check_range(header.sh_offset, header.sh_size)?;
+        reader.seek(SeekFrom::Start(header.sh_offset))?;
+
+        let nbuckets = reader.read_u32::<LittleEndian>()? as u64;
+        let symoffset = reader.read_u32::<LittleEndian>()? as usize;
+        let bloom_size = reader.read_u32::<LittleEndian>()? as u64;
+        let _bloom_shift = reader.read_u32::<LittleEndian>()?;
+
+        // bloom filter words are 64-bit in ELF64
+        let chain_offset = 16 + bloom_size * 8 + nbuckets * 4;
+
+        if chain_offset > header.sh_size {
+            return Err(ElfError::Malformed(String::from("GNU hash table size")));
+        }
+
+        reader.seek(SeekFrom::Current(bloom_size as i64 * 8))?;
+
+        let buckets = read_words(nbuckets, reader)?;
+        let chain = read_words((header.sh_size - chain_offset) / 4, reader)?;
+
+        let mut chains = vec![];
+
+        for bucket in buckets {
+            let mut length = 0;
+
+            if bucket as usize >= symoffset {
+                for value in chain.iter().skip(bucket as usize - symoffset) {
+                    length += 1;
+
+                    if value & 1 == 1 {
+                        break;
+                    }
+                }
+            }
+
+            chains.push(length);
+        }
+
+        Ok(chains)
+    }
+
+    fn average(&self) -> f64 {
+        let used = self.chains.iter().filter(|length| **length > 0).count();
+
+        match used {
+            0 => 0.0,
+            _ => self.chains.iter().sum::<usize>() as f64 / used as f64,
+        }
+    }
+}
+
+impl HashTables {
+    pub fn new(headers: &SectionHeaders, reader: &mut Reader) -> Result<HashTables> {
+        let mut data = vec![];
+
+        for header in &headers.headers {
+            let chains = match header.sh_type {
+                SectionHeaderType::Hash => HashTable::new_sysv(header, reader)?,
+                SectionHeaderType::GnuHash => HashTable::new_gnu(header, reader)?,
+                _ => continue,
+            };
+
+            data.push(HashTable {
+                name: headers.strtab.get(header.sh_name as u64),
+                chains,
+            });
+        }
+
+        Ok(HashTables { data })
+    }
+}
+
+impl fmt::Display for HashTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Hash table `{}' has {} buckets and {} symbols:",
+            self.name,
+            self.chains.len(),
+            self.chains.iter().sum::<usize>()
+        )?;
+        writeln!(
+            f,
+            "Average chain length: {:.2}, max chain length: {}",
+            self.average(),
+            self.chains.iter().max().unwrap_or(&0)
+        )
+    }
+}
+
+impl fmt::Display for HashTables {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.data.is_empty() {
+            return writeln!(f, "There are no hash tables in this file");
+        }
+
+        for table in &self.data {
+            table.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, ElfBuilder, Section};
+
+    // SHT_HASH, SHT_GNU_HASH
+    const HASH: u32 = 5;
+    const GNU_HASH: u32 = 0x6ffffff6;
+
+    fn words(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    fn hash_table(section: Section) -> HashTable {
+        // DYN, x86-64
+        let (header, mut reader) = parse(ElfBuilder::new(3, 62).section(section).build());
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+
+        HashTables::new(&sections, &mut reader)
+            .unwrap()
+            .data
+            .remove(0)
+    }
+
+    #[test]
+    fn sysv_chains() {
+        // the first bucket has symbols 1 and 2, the second is empty and
+        // the third loops between symbols 3 and 4
+        let data = words(&[3, 5, 1, 0, 3, 0, 2, 0, 4, 3]);
+        let table = hash_table(Section::new(".hash", HASH, data));
+
+        assert_eq!(table.chains, vec![2, 0, 5]);
+        assert_eq!(table.average(), 3.5);
+    }
+
+    #[test]
+    fn gnu_chains() {
+        // symoffset is 2, the second bucket is below it, the chains of
+        // the first and the third bucket have 2 and 1 symbols
+        let mut data = words(&[3, 2, 1, 0]);
+        data.extend(&0u64.to_le_bytes());
+        data.extend(words(&[2, 0, 4, 0x10, 0x11, 0x21]));
+
+        let table = hash_table(Section::new(".gnu.hash", GNU_HASH, data));
+
+        assert_eq!(table.chains, vec![2, 0, 1]);
+        assert_eq!(table.average(), 1.5);
+    }
+}
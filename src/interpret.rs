@@ -1,15 +1,29 @@
+use crate::error::Result;
 use crate::program::{ProgramHeaders, SegmentType};
-use crate::reader::{Reader, Seek, SeekFrom};
+use crate::reader::{Reader, ReaderExt};
+use crate::section::SectionHeaders;
 use std::fmt;
-use std::io::Read;
 
 #[derive(Debug)]
 pub struct Interpret {
     path: String,
 }
 
+fn read_path(offset: u64, size: u64, reader: &mut Reader) -> Result<String> {
+    if size == 0 {
+        return Ok(String::new());
+    }
+
+    // the path is stored with its null terminator
+    reader.read_cstr_in(offset, size)
+}
+
 impl Interpret {
-    pub fn new(headers: &ProgramHeaders, reader: &mut Reader) -> Interpret {
+    pub fn new(
+        headers: &ProgramHeaders,
+        sections: &SectionHeaders,
+        reader: &mut Reader,
+    ) -> Result<Interpret> {
         let mut path = String::from("");
 
         for header in &headers.headers {
@@ -17,16 +31,18 @@ impl Interpret {
                 continue;
             }
 
-            reader.seek(SeekFrom::Start(header.p_offset)).unwrap();
-
-            let mut data = vec![0; header.p_filesz as usize];
-            reader.read_exact(&mut data).unwrap();
-
-            path = String::from_utf8(data).unwrap();
+            path = read_path(header.p_offset, header.p_filesz, reader)?;
             break;
         }
 
-        Interpret { path }
+        // files without program headers may still have the section
+        if headers.get_all(SegmentType::Interp).is_empty() {
+            if let Some(header) = sections.get_by_name(".interp") {
+                path = read_path(header.sh_offset, header.file_size(), reader)?;
+            }
+        }
+
+        Ok(Interpret { path })
     }
 }
 
@@ -35,3 +51,65 @@ impl fmt::Display for Interpret {
         writeln!(f, "Interpret path: `{}'", self.path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, ElfBuilder, Section, Segment};
+
+    // SHT_PROGBITS, PT_INTERP
+    const PROGBITS: u32 = 1;
+    const INTERP: u32 = 3;
+
+    fn interpret(data: Vec<u8>) -> Result<Interpret> {
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader)?;
+        let segments = ProgramHeaders::new(&header, &mut reader)?;
+
+        Interpret::new(&segments, &sections, &mut reader)
+    }
+
+    #[test]
+    fn reads_segment() {
+        let path = b"/lib64/ld-linux-x86-64.so.2\0".to_vec();
+        // DYN, x86-64
+        let data = ElfBuilder::new(3, 62)
+            .segment(Segment::new(INTERP, path))
+            .build();
+
+        assert_eq!(interpret(data).unwrap().path, "/lib64/ld-linux-x86-64.so.2");
+    }
+
+    #[test]
+    fn reads_section() {
+        let path = b"/lib/ld-musl-x86_64.so.1\0".to_vec();
+        // REL, x86-64
+        let data = ElfBuilder::new(1, 62)
+            .section(Section::new(".interp", PROGBITS, path))
+            .build();
+
+        assert_eq!(interpret(data).unwrap().path, "/lib/ld-musl-x86_64.so.1");
+    }
+
+    #[test]
+    fn path_ends_with_segment() {
+        let mut segment = Segment::new(INTERP, b"/lib/ld.so\0".to_vec());
+        segment.p_filesz = Some(4);
+
+        let data = ElfBuilder::new(3, 62).segment(segment).build();
+
+        assert_eq!(interpret(data).unwrap().path, "/lib");
+    }
+
+    #[test]
+    fn segment_out_of_bounds() {
+        let mut segment = Segment::new(INTERP, vec![]);
+        segment.p_filesz = Some(16);
+
+        let mut data = ElfBuilder::new(3, 62).segment(segment).build();
+        // move the segment past the end of the file
+        data[64 + 8..64 + 16].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        assert!(interpret(data).is_err());
+    }
+}
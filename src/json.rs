@@ -0,0 +1,47 @@
+// Minimal helpers for printing tables as JSON, values are written by
+// hand to avoid pulling a serialization framework into the parsers
+
+// Quote and escape string value
+pub fn string(value: &str) -> String {
+    let mut result = String::from("\"");
+
+    for ch in value.chars() {
+        match ch {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => result.push(ch),
+        }
+    }
+
+    result.push('"');
+    result
+}
+
+// Object from already encoded values
+pub fn object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(name, value)| format!("{}: {}", string(name), value))
+        .collect();
+
+    format!("{{{}}}", fields.join(", "))
+}
+
+// Array from already encoded values
+pub fn array(values: &[String]) -> String {
+    format!("[{}]", values.join(", "))
+}
+
+// Encoded value or null when it is missing
+pub fn or_null(value: Option<String>) -> String {
+    value.unwrap_or_else(|| String::from("null"))
+}
+
+// 64-bit addresses do not fit into JSON numbers, use hex strings instead
+pub fn hex(value: u64) -> String {
+    string(&format!("{:#x}", value))
+}
@@ -0,0 +1,26 @@
+// Parsers of the ELF structures, the command line tool is built on top
+// of them only with the `cli' feature
+pub mod addrsig;
+pub mod attributes;
+pub mod checksum;
+pub mod count;
+pub mod debuglink;
+pub mod diff;
+pub mod dynamic;
+pub mod error;
+pub mod file;
+pub mod gobuildinfo;
+pub mod hash;
+pub mod interpret;
+pub mod json;
+pub mod locate;
+pub mod notes;
+pub mod program;
+pub mod reader;
+pub mod relocs;
+pub mod section;
+pub mod symbols;
+pub mod version;
+
+#[cfg(test)]
+mod fixture;
@@ -0,0 +1,146 @@
+use crate::file::ElfFileHeader;
+use crate::program::ProgramHeaders;
+use crate::section::{SectionHeaderType, SectionHeaders};
+use std::fmt;
+
+fn contains(start: u64, size: u64, offset: u64) -> bool {
+    offset >= start && offset - start < size
+}
+
+#[derive(Debug)]
+pub struct OffsetLocation {
+    // Looked up file offset
+    offset: u64,
+    // ELF header, program header table or section header table
+    header: Option<&'static str>,
+    // Names of sections that contain the offset
+    sections: Vec<String>,
+    // Segments (index and type) that contain the offset
+    segments: Vec<String>,
+}
+
+// Data appended after the end of the ELF structures
+#[derive(Debug)]
+pub struct Overlay {
+    // End of the headers, sections and segments
+    end: u64,
+    // Size of the file
+    len: u64,
+}
+
+impl OffsetLocation {
+    pub fn new(
+        offset: u64,
+        header: &ElfFileHeader,
+        sections: &SectionHeaders,
+        programs: &ProgramHeaders,
+    ) -> OffsetLocation {
+        let phsize = header.e_phnum as u64 * header.e_phentsize as u64;
+        let shsize = header.e_shnum as u64 * header.e_shentsize as u64;
+
+        let header_name = if contains(0, header.e_ehsize as u64, offset) {
+            Some("ELF header")
+        } else if contains(header.e_phoff, phsize, offset) {
+            Some("Program header table")
+        } else if contains(header.e_shoff, shsize, offset) {
+            Some("Section header table")
+        } else {
+            None
+        };
+
+        let mut section_names = vec![];
+
+        for section in &sections.headers {
+            if contains(section.sh_offset, section.file_size(), offset) {
+                section_names.push(sections.strtab.get(section.sh_name as u64));
+            }
+        }
+
+        let mut segments = vec![];
+
+        for (i, program) in programs.headers.iter().enumerate() {
+            if contains(program.p_offset, program.p_filesz, offset) {
+                segments.push(format!("[{:02}] {}", i, program.p_type));
+            }
+        }
+
+        OffsetLocation {
+            offset,
+            header: header_name,
+            sections: section_names,
+            segments,
+        }
+    }
+}
+
+impl Overlay {
+    pub fn new(
+        header: &ElfFileHeader,
+        sections: &SectionHeaders,
+        programs: &ProgramHeaders,
+        len: u64,
+    ) -> Overlay {
+        let phsize = header.e_phnum as u64 * header.e_phentsize as u64;
+        let shsize = header.e_shnum as u64 * header.e_shentsize as u64;
+
+        let mut end = header.e_ehsize as u64;
+        end = end.max(header.e_phoff.saturating_add(phsize));
+        end = end.max(header.e_shoff.saturating_add(shsize));
+
+        for section in &sections.headers {
+            // NOBITS sections don't occupy any space in the file
+            if section.sh_type != SectionHeaderType::Bss {
+                end = end.max(section.sh_offset.saturating_add(section.sh_size));
+            }
+        }
+
+        for program in &programs.headers {
+            end = end.max(program.p_offset.saturating_add(program.p_filesz));
+        }
+
+        Overlay { end, len }
+    }
+}
+
+impl fmt::Display for Overlay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.len <= self.end {
+            return writeln!(f, "No overlay, the file ends at {:#x}", self.end);
+        }
+
+        writeln!(
+            f,
+            "Overlay at offset {:#x}, {:#x} bytes",
+            self.end,
+            self.len - self.end
+        )
+    }
+}
+
+impl fmt::Display for OffsetLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Offset {:#x}:", self.offset)?;
+
+        if let Some(header) = self.header {
+            writeln!(f, "{:<16}{}", "Header:", header)?;
+        }
+
+        if self.sections.is_empty() {
+            writeln!(f, "{:<16}-", "Section:")?;
+        }
+
+        for section in &self.sections {
+            writeln!(f, "{:<16}{}", "Section:", section)?;
+        }
+
+        if self.segments.is_empty() {
+            writeln!(f, "{:<16}-", "Segment:")?;
+        }
+
+        for segment in &self.segments {
+            writeln!(f, "{:<16}{}", "Segment:", segment)?;
+        }
+
+        Ok(())
+    }
+}
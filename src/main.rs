@@ -1,22 +1,28 @@
-mod dynamic;
-mod error;
-mod file;
-mod interpret;
-mod notes;
-mod program;
-mod reader;
-mod relocs;
-mod section;
-mod symbols;
-mod version;
+use rust_elf::{
+    addrsig, attributes, checksum, count, debuglink, diff, dynamic, file, gobuildinfo, hash,
+    interpret, locate, notes, program, reader, relocs, section, symbols, version,
+};
+
 mod elf;
 
-use std::path::PathBuf;
-use structopt::StructOpt;
 use anyhow::Result;
 use elf::Elf;
+use file::HeaderQuery;
+use reader::InputRange;
+use section::SectionOrder;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use structopt::StructOpt;
+use symbols::SymbolFilter;
 
 #[derive(Debug, StructOpt)]
+#[structopt(after_help = "EXIT STATUS:
+    0  success
+    1  --check, --endianness-report, --segments-permissions or --security
+       found problems, or a queried value is missing
+    2  a file could not be read or parsed")]
 struct DisplayOptions {
     #[structopt(
         short = "a",
@@ -46,15 +52,124 @@ struct DisplayOptions {
     )]
     section_headers: bool,
 
+    #[structopt(
+        long = "sort-sections",
+        help = "Display the section headers sorted by addr, offset, size or name"
+    )]
+    sort_sections: Option<SectionOrder>,
+
+    #[structopt(
+        long = "verbose",
+        help = "Explain sh_link and sh_info fields in the section headers"
+    )]
+    verbose: bool,
+
+    #[structopt(
+        long = "alloc-only",
+        help = "List only sections occupying memory at run time in the section headers"
+    )]
+    alloc_only: bool,
+
+    #[structopt(
+        long = "sections-by-type",
+        help = "Display number and total size of sections of each type"
+    )]
+    sections_by_type: bool,
+
     #[structopt(short = "s", long = "symbols", help = "Display the symbol table")]
     symbols: bool,
 
+    #[structopt(
+        long = "undefined-only",
+        help = "Display only undefined symbols in the symbol table",
+        conflicts_with = "defined-only"
+    )]
+    undefined_only: bool,
+
+    #[structopt(
+        long = "defined-only",
+        help = "Display only defined symbols in the symbol table"
+    )]
+    defined_only: bool,
+
+    #[structopt(
+        long = "dyn-syms",
+        help = "Display the dynamic symbol table and which symbols are hashed"
+    )]
+    dyn_syms: bool,
+
+    #[structopt(
+        long = "size",
+        help = "Display the total size of code, data and bss like size(1)"
+    )]
+    size: bool,
+
+    #[structopt(
+        long = "symbol-xref",
+        help = "Display which symbols are in .symtab, .dynsym or both"
+    )]
+    symbol_xref: bool,
+
+    #[structopt(long = "nm", help = "Display the symbol table in the format of nm")]
+    nm: bool,
+
+    #[structopt(
+        long = "stream-symbols",
+        help = "Display the symbol table while reading it, for very large tables"
+    )]
+    stream_symbols: bool,
+
+    #[structopt(
+        long = "exports",
+        help = "Display the defined global symbols of the dynamic symbol table"
+    )]
+    exports: bool,
+
+    #[structopt(
+        long = "demangle",
+        help = "Demangle Rust and C++ symbol names in -s, --dyn-syms and --exports"
+    )]
+    demangle: bool,
+
+    #[structopt(
+        long = "imports",
+        help = "Display the undefined dynamic symbols and libraries they are expected from"
+    )]
+    imports: bool,
+
+    #[structopt(
+        long = "find-symbol-version",
+        help = "Display the version the dynamic symbol with the given name binds to"
+    )]
+    find_symbol_version: Option<String>,
+
     #[structopt(long = "notes", help = "Display notes")]
     notes: bool,
 
+    #[structopt(
+        long = "json",
+        help = "Print notes as JSON, or the whole file together with -a"
+    )]
+    json: bool,
+
+    #[structopt(
+        long = "all-json",
+        help = "Display the whole file as a single JSON document"
+    )]
+    all_json: bool,
+
+    #[structopt(long = "build-id", help = "Display the GNU build ID")]
+    build_id: bool,
+
     #[structopt(short = "d", long = "dynamic", help = "Display the dynamic section")]
     dynamic: bool,
 
+    #[structopt(
+        long = "segment-contents",
+        help = "Display the dynamic segment without using the section headers"
+    )]
+    dynamic_segment: bool,
+
     #[structopt(
         short = "V",
         long = "version-info",
@@ -69,30 +184,358 @@ struct DisplayOptions {
     )]
     interpret: bool,
 
+    #[structopt(long = "plt", help = "Display the PLT stubs and their target symbols")]
+    plt: bool,
+
+    #[structopt(
+        long = "plt-got",
+        help = "Display the PLT stub, GOT slot and versioned symbol of each imported function"
+    )]
+    plt_got: bool,
+
+    #[structopt(
+        long = "got",
+        help = "Display the GOT slots, their relocations and RELRO protection"
+    )]
+    got: bool,
+
+    #[structopt(long = "tls", help = "Display the thread-local storage size")]
+    tls: bool,
+
+    #[structopt(long = "count", help = "Display number of entries in each table")]
+    count: bool,
+
+    #[structopt(
+        long = "hash-stats",
+        help = "Display average and maximal chain length of the symbol hash tables"
+    )]
+    hash_stats: bool,
+
+    #[structopt(
+        long = "layout",
+        help = "Display address ranges of loadable segments and gaps between them"
+    )]
+    layout: bool,
+
+    #[structopt(
+        long = "segments-permissions",
+        help = "Display segments that are both writable and executable"
+    )]
+    segments_permissions: bool,
+
+    #[structopt(
+        long = "security",
+        alias = "checksec",
+        help = "Display security related properties of the file"
+    )]
+    security: bool,
+
     #[structopt(short = "r", long = "relocs", help = "Display the relocations")]
     relocs: bool,
 
-    #[structopt(parse(from_os_str))]
-    file: PathBuf,
+    #[structopt(
+        long = "reloc-values",
+        help = "Display the values written by the relocations, assuming load base 0 (x86-64 only)"
+    )]
+    reloc_values: bool,
+
+    #[structopt(
+        short = "A",
+        long = "arch-attributes",
+        help = "Display the architecture specific attributes"
+    )]
+    arch_attributes: bool,
+
+    #[structopt(
+        long = "debuglink",
+        help = "Display the location of separate debug information"
+    )]
+    debuglink: bool,
+
+    #[structopt(
+        long = "verify-debuglink",
+        help = "Find the debug file named by the debug link and verify its CRC",
+        parse(from_os_str)
+    )]
+    verify_debuglink: Option<PathBuf>,
+
+    #[structopt(
+        long = "follow-build-id",
+        value_name = "DIR",
+        help = "Find the debug file named after the build ID in DIR, given as \
+                --follow-build-id=DIR, and in /usr/lib/debug",
+        min_values = 0,
+        require_equals = true
+    )]
+    follow_build_id: Option<Option<PathBuf>>,
+
+    #[structopt(long = "addrsig", help = "Display the address-significance table")]
+    addrsig: bool,
+
+    #[structopt(
+        long = "go-buildinfo",
+        help = "Display the Go version and main module from the Go build info"
+    )]
+    go_buildinfo: bool,
+
+    #[structopt(
+        long = "addr2sym",
+        help = "Display the symbol that contains the address",
+        parse(try_from_str = parse_offset)
+    )]
+    addr2sym: Option<u64>,
+
+    #[structopt(
+        long = "offset-to-section",
+        help = "Display the section and segment that contain the file offset",
+        parse(try_from_str = parse_offset)
+    )]
+    offset_to_section: Option<u64>,
+
+    #[structopt(
+        long = "overlay",
+        help = "Display data appended after the last section, segment and header table"
+    )]
+    overlay: bool,
+
+    #[structopt(
+        long = "diff",
+        help = "Display structural differences against another ELF file",
+        parse(from_os_str)
+    )]
+    diff: Option<PathBuf>,
+
+    #[structopt(
+        long = "raw-header",
+        help = "Display annotated hex dump of the file header"
+    )]
+    raw_header: bool,
+
+    #[structopt(long = "checksums", help = "Display CRC32 of contents of each section")]
+    checksums: bool,
+
+    #[structopt(
+        long = "check",
+        alias = "first-mismatch",
+        help = "Check that identification padding and versions have standard values"
+    )]
+    check: bool,
+
+    #[structopt(
+        long = "endianness-report",
+        help = "Check whether the header makes sense in the declared byte order"
+    )]
+    endianness_report: bool,
+
+    #[structopt(
+        long = "input-range",
+        help = "Treat only bytes START:END of the input as the file"
+    )]
+    input_range: Option<InputRange>,
+
+    #[structopt(
+        long = "best-effort",
+        help = "Display entries of truncated tables up to the end of the file"
+    )]
+    best_effort: bool,
+
+    #[structopt(
+        long = "truncate-strings",
+        value_name = "N",
+        help = "Cut symbol and section names longer than N characters in listings"
+    )]
+    truncate_strings: Option<usize>,
+
+    // Query options, each prints a single value and nothing else
+    #[structopt(long = "entry", help = "Print only the entry point address")]
+    entry: bool,
+
+    #[structopt(long = "machine", help = "Print only the machine name")]
+    machine: bool,
+
+    #[structopt(long = "class", help = "Print only the file class (32 or 64)")]
+    class: bool,
+
+    #[structopt(
+        long = "type",
+        help = "Print only the object type (REL, EXEC, DYN or CORE)"
+    )]
+    object_type: bool,
+
+    #[structopt(
+        long = "watch",
+        help = "Display the file again whenever it changes, until interrupted"
+    )]
+    watch: bool,
+
+    #[structopt(parse(from_os_str), required = true)]
+    files: Vec<PathBuf>,
 }
 
+// How often the watched file is checked for changes
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
 
+// Exit status when --check, --endianness-report, --segments-permissions or
+// --security found problems, queries use it too when the file does not
+// specify the value
+const EXIT_PROBLEMS: i32 = 1;
+// Exit status when a file could not be read or parsed
+const EXIT_ERROR: i32 = 2;
 
-fn main() -> Result<()> {
+fn parse_offset(src: &str) -> Result<u64, std::num::ParseIntError> {
+    match src.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => src.parse(),
+    }
+}
 
+fn main() {
     let options = DisplayOptions::from_args();
-    let elf = Elf::new(options.file)?;
+
+    if options.watch {
+        watch(&options);
+    }
+
+    std::process::exit(display_all(&options));
+}
+
+// Display the files one after another, each is preceded by its name
+// when there are more of them, a failure is reported and the next file
+// is displayed anyway, returns the exit status for all of the files
+fn display_all(options: &DisplayOptions) -> i32 {
+    let mut status = 0;
+
+    for (n, file) in options.files.iter().enumerate() {
+        if options.files.len() > 1 {
+            if n > 0 {
+                println!();
+            }
+            println!("File: {}", file.display());
+        }
+
+        match display(options, file) {
+            Ok(true) => {}
+            Ok(false) => status = status.max(EXIT_PROBLEMS),
+            Err(error) => {
+                eprintln!("Error: {}", error);
+                status = EXIT_ERROR;
+            }
+        }
+    }
+
+    status
+}
+
+// Clear the screen and display the files again whenever a modification
+// time changes, a file may be missing or half-written during a rebuild
+// so they are displayed only after the times stay the same for one
+// interval and failures are reported without leaving the loop
+fn watch(options: &DisplayOptions) -> ! {
+    let mut previous = vec![];
+    let mut shown = vec![];
+
+    loop {
+        let modified: Vec<_> = options
+            .files
+            .iter()
+            .map(|file| {
+                std::fs::metadata(file)
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect();
+
+        if modified.iter().all(Option::is_some) && modified == previous && modified != shown {
+            shown = modified.clone();
+
+            // clear the screen and move the cursor to the top left corner
+            print!("\x1b[2J\x1b[H");
+
+            display_all(options);
+
+            let _ = std::io::stdout().flush();
+        }
+
+        previous = modified;
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+// Display the file, returns false when a check found problems or a
+// queried value is missing
+fn display(options: &DisplayOptions, file: &Path) -> Result<bool> {
+    let mut elf = match options.input_range {
+        Some(range) => Elf::new_in_range(file.to_path_buf(), range)?,
+        None => Elf::new(file.to_path_buf())?,
+    };
+
+    elf.set_best_effort(options.best_effort);
+    elf.set_width(options.truncate_strings);
+    elf.set_demangle(options.demangle);
+
+    let mut passed = true;
+
+    let queries = [
+        (options.entry, HeaderQuery::Entry),
+        (options.machine, HeaderQuery::Machine),
+        (options.class, HeaderQuery::Class),
+        (options.object_type, HeaderQuery::Type),
+    ];
+
+    if queries.iter().any(|(enabled, _)| *enabled) {
+        for (_, query) in queries.iter().filter(|(enabled, _)| *enabled) {
+            match elf.query(*query) {
+                Some(value) => println!("{}", value),
+                None => passed = false,
+            }
+        }
+
+        return Ok(passed);
+    }
+
+    if options.all_json || (options.all && options.json) {
+        elf.show_all_json()?;
+        return Ok(true);
+    }
 
     if options.file_header || options.all {
         elf.show_file_header()?;
     }
 
+    if options.raw_header {
+        elf.show_raw_header()?;
+    }
+
+    if options.check {
+        passed &= elf.show_header_check()?;
+    }
+
+    if options.endianness_report {
+        passed &= elf.show_endianness_report()?;
+    }
+
+    if options.checksums {
+        elf.show_checksums()?;
+    }
+
     if options.program_headers || options.all {
         elf.show_program_headers()?;
     }
 
-    if options.section_headers || options.all {
-        elf.show_section_headers()?;
+    if options.sort_sections.is_some()
+        || (options.verbose && (options.section_headers || options.all))
+    {
+        elf.show_sorted_section_headers(
+            options.sort_sections,
+            options.verbose,
+            options.alloc_only,
+        )?;
+    } else if options.section_headers || options.all {
+        elf.show_section_headers(options.alloc_only)?;
+    }
+
+    if options.sections_by_type {
+        elf.show_sections_by_type()?;
     }
 
     if options.interpret || options.all {
@@ -100,15 +543,72 @@ fn main() -> Result<()> {
     }
 
     if options.symbols || options.all {
-        elf.show_symbols()?;
+        // ifunc symbols are linked to IRELATIVE relocations only when
+        // the relocations are displayed as well
+        let filter = if options.undefined_only {
+            Some(SymbolFilter::Undefined)
+        } else if options.defined_only {
+            Some(SymbolFilter::Defined)
+        } else {
+            None
+        };
+
+        elf.show_symbols(options.relocs || options.all, filter)?;
+    }
+
+    if options.dyn_syms {
+        elf.show_dynamic_symbols()?;
+    }
+
+    if options.size {
+        elf.show_size(file)?;
+    }
+
+    if options.symbol_xref {
+        elf.show_symbol_xref()?;
+    }
+
+    if options.nm {
+        elf.show_nm_symbols()?;
+    }
+
+    if options.stream_symbols {
+        elf.write_symbols(&mut std::io::stdout().lock())?;
+    }
+
+    if options.exports {
+        elf.show_exports()?;
+    }
+
+    if options.imports {
+        elf.show_imports()?;
+    }
+
+    if let Some(name) = &options.find_symbol_version {
+        elf.show_symbol_version(name)?;
     }
 
     if options.dynamic || options.all {
         elf.show_dynamic()?;
     }
 
+    if options.dynamic_segment {
+        elf.show_dynamic_segment()?;
+    }
+
     if options.notes || options.all {
-        elf.show_notes()?;
+        if options.json {
+            elf.show_notes_json()?;
+        } else {
+            elf.show_notes()?;
+        }
+    }
+
+    if options.build_id {
+        match elf.build_id()? {
+            Some(id) => println!("Build ID: {}", id),
+            None => println!("No build ID found"),
+        }
     }
 
     if options.version_info || options.all {
@@ -116,8 +616,94 @@ fn main() -> Result<()> {
     }
 
     if options.relocs || options.all {
-        elf.show_relocs()?;
+        elf.show_relocs(options.reloc_values)?;
+    }
+
+    if options.plt {
+        elf.show_plt()?;
+    }
+
+    if options.plt_got {
+        elf.show_plt_trace()?;
+    }
+
+    if options.got {
+        elf.show_got()?;
+    }
+
+    if options.tls {
+        elf.show_tls_size()?;
+    }
+
+    if options.count {
+        elf.show_counts()?;
+    }
+
+    if options.hash_stats {
+        elf.show_hash_stats()?;
+    }
+
+    if options.layout {
+        elf.show_segment_layout()?;
+    }
+
+    if options.segments_permissions || options.security {
+        passed &= elf.show_segments_permissions()?;
+    }
+
+    if options.security {
+        elf.show_pie()?;
+        passed &= elf.show_text_relocations(file)?;
+        elf.show_feature_properties()?;
+    }
+
+    if options.arch_attributes {
+        elf.show_arch_attributes()?;
+    }
+
+    if options.debuglink {
+        elf.show_debuglink()?;
+    }
+
+    if let Some(dir) = &options.verify_debuglink {
+        println!("{}", elf.verify_debuglink(file, dir)?);
+    }
+
+    if let Some(dir) = &options.follow_build_id {
+        let mut dirs: Vec<PathBuf> = dir.iter().cloned().collect();
+        dirs.push(PathBuf::from(debuglink::DEFAULT_DEBUG_DIR));
+        dirs.dedup();
+
+        print!(
+            "{}",
+            debuglink::BuildIdDebugFile::new(elf.build_id()?, &dirs)
+        );
+    }
+
+    if options.addrsig {
+        elf.show_addrsig()?;
+    }
+
+    if options.go_buildinfo {
+        elf.show_go_buildinfo()?;
+    }
+
+    if let Some(address) = options.addr2sym {
+        elf.show_symbol_at(address)?;
+    }
+
+    if let Some(offset) = options.offset_to_section {
+        println!("{}", elf.locate_offset(offset)?);
+    }
+
+    if options.overlay {
+        elf.show_overlay()?;
+    }
+
+    if let Some(path) = &options.diff {
+        let other = Elf::new(path.clone())?;
+        println!("{}", elf.diff(&other)?);
     }
 
-    Ok(())
+    Ok(passed)
 }
@@ -1,9 +1,11 @@
+use crate::error::{ElfError, Result};
+use crate::file::ObjectType;
+use crate::json;
 use crate::program::{ProgramHeader, ProgramHeaders, SegmentType};
-use crate::reader::{Cursor, LittleEndian, ReadBytesExt, Reader, Seek, SeekFrom};
+use crate::reader::{cstr, Cursor, LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
 use crate::section::{SectionHeader, SectionHeaderType, SectionHeaders};
-use std::io::Read;
-use anyhow::{Result, Context, bail};
 use std::fmt;
+use std::io::Read;
 
 fn align_up(size: u64, align: u64) -> u64 {
     /* Some PT_NOTE segment may have alignment value of 0
@@ -25,6 +27,9 @@ fn align_up(size: u64, align: u64) -> u64 {
  */
 const ELF_NOTE_SIZE: u64 = 3 * 4;
 
+// Alignment of names and descriptors in core dump notes
+const CORE_NOTE_ALIGN: u64 = 4;
+
 fn note_desc_offset(namesz: u64, align: u64) -> u64 {
     align_up(ELF_NOTE_SIZE + namesz, align)
 }
@@ -33,6 +38,38 @@ fn note_next_offset(namesz: u64, descsz: u64, align: u64) -> u64 {
     align_up(note_desc_offset(namesz, align) + descsz, align)
 }
 
+// GNU property types with feature bits that are set only when all
+// linked objects support the feature
+const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc0000000;
+const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc0000002;
+
+/* NT_GNU_PROPERTY_TYPE_0 descriptor is an array of properties
+ * typedef struct {
+ *    Elf_Word pr_type;
+ *    Elf_Word pr_datasz;
+ *    unsigned char pr_data[pr_datasz];
+ * } Elf_Prop;
+ * each property is padded to 8 bytes in 64-bit objects
+ */
+fn find_property(data: &[u8], pr_type: u32) -> Option<u32> {
+    let mut reader = Cursor::new(data);
+
+    while let (Ok(kind), Ok(size)) = (
+        reader.read_u32::<LittleEndian>(),
+        reader.read_u32::<LittleEndian>(),
+    ) {
+        let start = reader.position();
+
+        if kind == pr_type && size == 4 {
+            return reader.read_u32::<LittleEndian>().ok();
+        }
+
+        reader.set_position(align_up(start + size as u64, 8));
+    }
+
+    None
+}
+
 fn to_hex_string(bytes: Vec<u8>) -> String {
     let strs: Vec<String> = bytes.iter().map(|b| format!("{:02X}", b)).collect();
     strs.join(" ")
@@ -108,6 +145,31 @@ enum NoteType {
     // x86 extended state using xsave
     X86ExtendedState,
 
+    // Note types of other owners
+
+    // __FreeBSD_version the object was built for
+    FreeBsdAbiTag,
+    // Startup code does not need the crt init
+    FreeBsdNoInit,
+    // Architecture the object was built for
+    FreeBsdArchTag,
+    // Opt-in/out of security features
+    FreeBsdFeatureCtl,
+    // __NetBSD_Version__ the object was built for
+    NetBsdIdent,
+    // PaX security flags
+    NetBsdPax,
+    // Machine architecture
+    NetBsdMarch,
+    // OpenBSD object marker
+    OpenBsdIdent,
+    // Go toolchain build ID
+    GoBuildId,
+    // Android API level the object was built for
+    AndroidIdent,
+    // JSON package metadata (.note.package)
+    FdoPackagingMetadata,
+
     // Note types for object files
     Version,
 
@@ -125,11 +187,125 @@ struct MappedFile {
 
 #[derive(Debug)]
 struct MappedFiles {
-    count: u64,
     pagesize: u64,
     files: Vec<MappedFile>,
 }
 
+// Names of general purpose registers as stored in x86-64 prstatus
+const X86_64_REGISTERS: [&str; 27] = [
+    "r15", "r14", "r13", "r12", "rbp", "rbx", "r11", "r10", "r9", "r8", "rax", "rcx", "rdx", "rsi",
+    "rdi", "orig_rax", "rip", "cs", "eflags", "rsp", "ss", "fs_base", "gs_base", "ds", "es", "fs",
+    "gs",
+];
+
+/* Layout of 64-bit Linux prstatus:
+ * struct elf_prstatus {
+ *    struct elf_siginfo pr_info;   // 0
+ *    short pr_cursig;              // 12
+ *    unsigned long pr_sigpend;     // 16
+ *    unsigned long pr_sighold;     // 24
+ *    pid_t pr_pid;                 // 32
+ *    pid_t pr_ppid;                // 36
+ *    pid_t pr_pgrp;                // 40
+ *    pid_t pr_sid;                 // 44
+ *    struct timeval pr_utime;      // 48
+ *    struct timeval pr_stime;      // 64
+ *    struct timeval pr_cutime;     // 80
+ *    struct timeval pr_cstime;     // 96
+ *    elf_gregset_t pr_reg;         // 112
+ *    int pr_fpvalid;
+ * };
+ */
+const PRSTATUS_REGS_OFFSET: usize = 112;
+
+#[derive(Debug)]
+struct PrStatus {
+    // Current signal
+    signal: u16,
+    // Thread (LWP) id
+    pid: u32,
+    // Parent process id
+    ppid: u32,
+    // Process group id
+    pgrp: u32,
+    // Session id
+    sid: u32,
+    // General purpose registers
+    registers: Vec<u64>,
+}
+
+/* Common prefix of Linux siginfo_t:
+ * typedef struct {
+ *    int si_signo;                 // 0
+ *    int si_errno;                 // 4
+ *    int si_code;                  // 8
+ *    union {                       // 16 (12 in 32-bit layout)
+ *        struct {
+ *            pid_t si_pid;
+ *            uid_t si_uid;
+ *        } kill;
+ *        struct {
+ *            void *si_addr;
+ *        } sigfault;
+ *        ...
+ *    };
+ * } siginfo_t;
+ */
+const SIGINFO_SIZE: usize = 12;
+
+// Signals that fill in the faulting address (si_addr)
+const FAULT_SIGNALS: [i32; 5] = [4, 5, 7, 8, 11];
+
+// Names of Linux signals, indexed by the signal number
+const SIGNAL_NAMES: [&str; 32] = [
+    "",
+    "SIGHUP",
+    "SIGINT",
+    "SIGQUIT",
+    "SIGILL",
+    "SIGTRAP",
+    "SIGABRT",
+    "SIGBUS",
+    "SIGFPE",
+    "SIGKILL",
+    "SIGUSR1",
+    "SIGSEGV",
+    "SIGUSR2",
+    "SIGPIPE",
+    "SIGALRM",
+    "SIGTERM",
+    "SIGSTKFLT",
+    "SIGCHLD",
+    "SIGCONT",
+    "SIGSTOP",
+    "SIGTSTP",
+    "SIGTTIN",
+    "SIGTTOU",
+    "SIGURG",
+    "SIGXCPU",
+    "SIGXFSZ",
+    "SIGVTALRM",
+    "SIGPROF",
+    "SIGWINCH",
+    "SIGIO",
+    "SIGPWR",
+    "SIGSYS",
+];
+
+#[derive(Debug)]
+struct SigInfo {
+    // Signal number
+    signo: i32,
+    // Errno value associated with the signal
+    errno: i32,
+    // Why the signal was sent, values <= 0 mean it came from a process
+    code: i32,
+    // Faulting address of SIGSEGV, SIGBUS, SIGILL, SIGFPE and SIGTRAP
+    addr: Option<u64>,
+    // Pid and uid of the process that sent the signal
+    sender: Option<(u32, u32)>,
+}
+
 #[derive(Debug)]
 enum NoteDesc {
     // ABI information
@@ -161,7 +337,18 @@ enum NoteDesc {
     GnuGoldVersion(String),
     // Program property
     GnuProperty(Vec<u8>),
+    // __FreeBSD_version, e.g. 1400097 for 14.0
+    FreeBsdAbiTag(u32),
+    // __NetBSD_Version__, e.g. 1000000000 for 10.0
+    NetBsdIdent(u32),
+    OpenBsdIdent(u32),
+    GoBuildId(String),
+    AndroidIdent(u32),
+    // Package name, version and other metadata as a JSON object
+    FdoPackagingMetadata(String),
     MappedFiles(MappedFiles),
+    PrStatus(PrStatus),
+    SigInfo(SigInfo),
     Unknown(Vec<u8>),
 }
 
@@ -171,6 +358,8 @@ enum NoteDesc {
 struct NoteSection {
     data: Vec<Note>,
     name: String,
+    // File ended before the end of the section
+    truncated: bool,
 }
 
 #[derive(Debug)]
@@ -178,6 +367,14 @@ pub struct NoteSections {
     data: Vec<NoteSection>,
 }
 
+// Control-flow protection features from the GNU property note,
+// x86 IBT/SHSTK or AArch64 BTI/PAC
+#[derive(Debug)]
+pub struct FeatureProperties {
+    machine: u16,
+    features: Option<u32>,
+}
+
 #[derive(Debug)]
 enum NoteOs {
     Linux,
@@ -191,16 +388,29 @@ enum NoteOs {
 enum NoteOwner {
     Gnu,
     Core,
-    // FreeBSD, NetBSD, ...
+    FreeBsd,
+    NetBsd,
+    OpenBsd,
+    Go,
+    Android,
+    Fdo,
     Unknown,
 }
 
 impl NoteOwner {
     fn new(name: &str) -> NoteOwner {
         use NoteOwner::*;
-        match name {
-            "GNU\0" => Gnu,
-            "LINUX\0" | "CORE\0" => Core,
+
+        // Go pads its owner name with NUL bytes to 4 bytes
+        match name.trim_end_matches('\0') {
+            "GNU" => Gnu,
+            "LINUX" | "CORE" => Core,
+            "FreeBSD" => FreeBsd,
+            "NetBSD" => NetBsd,
+            "OpenBSD" => OpenBsd,
+            "Go" => Go,
+            "Android" => Android,
+            "FDO" => Fdo,
             _ => Unknown,
         }
     }
@@ -213,6 +423,7 @@ impl Note {
 
         let type_ = reader.read_u32::<LittleEndian>()?;
 
+        reader.check_range(reader.position(), name_size as u64)?;
         let mut name_ = vec![0; name_size as usize];
         reader.read_exact(&mut name_)?;
 
@@ -221,6 +432,7 @@ impl Note {
 
         reader.seek(SeekFrom::Current(off as i64))?;
 
+        reader.check_range(reader.position(), desc_size as u64)?;
         let mut desc_ = vec![0; desc_size as usize];
         reader.read_exact(&mut desc_)?;
 
@@ -230,13 +442,21 @@ impl Note {
         let note_type = match owner {
             NoteOwner::Gnu => NoteType::gnu(type_),
             NoteOwner::Core => NoteType::core(type_),
+            NoteOwner::FreeBsd => NoteType::freebsd(type_),
+            NoteOwner::NetBsd => NoteType::netbsd(type_),
+            NoteOwner::OpenBsd => NoteType::openbsd(type_),
+            NoteOwner::Go => NoteType::go(type_),
+            NoteOwner::Android => NoteType::android(type_),
+            NoteOwner::Fdo => NoteType::fdo(type_),
             NoteOwner::Unknown => NoteType::default(type_),
         };
 
         let desc = match owner {
             NoteOwner::Gnu => NoteDesc::gnu(&note_type, desc_),
             NoteOwner::Core => NoteDesc::core(&note_type, desc_, addrsize)?,
+            NoteOwner::Fdo => NoteDesc::fdo(&note_type, desc_),
             NoteOwner::Unknown => NoteDesc::default(desc_),
+            _ => NoteDesc::os(&note_type, desc_),
         };
 
         Ok(Note {
@@ -289,6 +509,57 @@ impl NoteType {
         }
     }
 
+    fn freebsd(value: u32) -> NoteType {
+        use NoteType::*;
+
+        match value {
+            1 => FreeBsdAbiTag,
+            2 => FreeBsdNoInit,
+            3 => FreeBsdArchTag,
+            4 => FreeBsdFeatureCtl,
+            _ => Unknown(value),
+        }
+    }
+
+    fn netbsd(value: u32) -> NoteType {
+        use NoteType::*;
+
+        match value {
+            1 => NetBsdIdent,
+            3 => NetBsdPax,
+            5 => NetBsdMarch,
+            _ => Unknown(value),
+        }
+    }
+
+    fn openbsd(value: u32) -> NoteType {
+        match value {
+            1 => NoteType::OpenBsdIdent,
+            _ => NoteType::Unknown(value),
+        }
+    }
+
+    fn go(value: u32) -> NoteType {
+        match value {
+            4 => NoteType::GoBuildId,
+            _ => NoteType::Unknown(value),
+        }
+    }
+
+    fn android(value: u32) -> NoteType {
+        match value {
+            1 => NoteType::AndroidIdent,
+            _ => NoteType::Unknown(value),
+        }
+    }
+
+    fn fdo(value: u32) -> NoteType {
+        match value {
+            0xcafe1a7e => NoteType::FdoPackagingMetadata,
+            _ => NoteType::Unknown(value),
+        }
+    }
+
     fn default(value: u32) -> NoteType {
         use NoteType::*;
 
@@ -297,43 +568,40 @@ impl NoteType {
             _ => NoteType::Unknown(value),
         }
     }
+
+    // Core dump notes describing the whole process rather than one of
+    // its threads
+    fn is_process_wide(&self) -> bool {
+        use NoteType::*;
+
+        matches!(
+            self,
+            PrPsInfo | Platform | Auxw | PsStatus | PsInfo | PrcRed | UtsName | MappedFiles
+        )
+    }
 }
 
 fn read_filenames(reader: &mut Reader, count: u64, addrsize: u64) -> Result<Vec<String>> {
     let mut result = Vec::new();
-    let mut buffer = [0; 1];
-    let mut current = String::new();
 
     let start = (2 * addrsize) + // count + pagesize items
                 (count * 3 * addrsize); // start, end, offset for each mapped file
 
     reader.seek(SeekFrom::Start(start))?;
     for _ in 0..count {
-        // read name until we read null byte
-        loop {
-            reader.read_exact(&mut buffer)?;
-
-            if buffer[0] == 0 {
-                break;
-            }
-
-            current.push(buffer[0] as char);
-        }
-
-        result.push(current.clone());
-        current.clear();
+        let offset = reader.position();
+        result.push(reader.read_cstr_at(offset)?);
     }
     Ok(result)
 }
 
 impl MappedFiles {
-
     fn new(data: Vec<u8>, addrsize: u8) -> Result<MappedFiles> {
         let readaddr = |reader: &mut Reader| -> Result<u64> {
             match addrsize {
                 4 => Ok(reader.read_u32::<LittleEndian>()? as u64),
                 8 => Ok(reader.read_u64::<LittleEndian>()?),
-                _ => bail!("invalid addrsize: {}", addrsize),
+                _ => Err(ElfError::UnsupportedClass(addrsize)),
             }
         };
 
@@ -353,19 +621,112 @@ impl MappedFiles {
                 start: readaddr(&mut reader)?,
                 end: readaddr(&mut reader)?,
                 page_offset: readaddr(&mut reader)?,
-                filename: filenames.get(idx as usize)
-                                   .context("Unable to find filename")?.clone(),
+                filename: filenames
+                    .get(idx as usize)
+                    .ok_or_else(|| ElfError::Malformed("mapped files note".to_string()))?
+                    .clone(),
             });
         }
 
-        Ok(MappedFiles {
-            count,
-            pagesize,
-            files,
+        Ok(MappedFiles { pagesize, files })
+    }
+}
+
+impl PrStatus {
+    fn new(data: Vec<u8>) -> Result<PrStatus> {
+        if data.len() < PRSTATUS_REGS_OFFSET + 8 {
+            return Err(ElfError::Malformed(format!(
+                "prstatus note of {} bytes",
+                data.len()
+            )));
+        }
+
+        let mut reader = Cursor::new(data);
+
+        reader.seek(SeekFrom::Start(12))?;
+        let signal = reader.read_u16::<LittleEndian>()?;
+
+        reader.seek(SeekFrom::Start(32))?;
+        let pid = reader.read_u32::<LittleEndian>()?;
+        let ppid = reader.read_u32::<LittleEndian>()?;
+        let pgrp = reader.read_u32::<LittleEndian>()?;
+        let sid = reader.read_u32::<LittleEndian>()?;
+
+        // registers are followed by pr_fpvalid and padding
+        let count = (reader.get_ref().len() - PRSTATUS_REGS_OFFSET - 8) / 8;
+        let mut registers = vec![];
+
+        reader.seek(SeekFrom::Start(PRSTATUS_REGS_OFFSET as u64))?;
+        for _ in 0..count {
+            registers.push(reader.read_u64::<LittleEndian>()?);
+        }
+
+        Ok(PrStatus {
+            signal,
+            pid,
+            ppid,
+            pgrp,
+            sid,
+            registers,
         })
     }
 }
 
+impl SigInfo {
+    fn new(data: Vec<u8>, addrsize: u8) -> Result<SigInfo> {
+        if data.len() < SIGINFO_SIZE {
+            return Err(ElfError::Malformed(format!(
+                "siginfo note of {} bytes",
+                data.len()
+            )));
+        }
+
+        let mut reader = Cursor::new(data);
+
+        let signo = reader.read_i32::<LittleEndian>()?;
+        let errno = reader.read_i32::<LittleEndian>()?;
+        let code = reader.read_i32::<LittleEndian>()?;
+
+        // the union is aligned to the pointer size
+        reader.seek(SeekFrom::Start(if addrsize == 8 { 16 } else { 12 }))?;
+
+        let mut addr = None;
+        let mut sender = None;
+
+        if FAULT_SIGNALS.contains(&signo) && code > 0 {
+            addr = match addrsize {
+                8 => reader.read_u64::<LittleEndian>().ok(),
+                _ => reader
+                    .read_u32::<LittleEndian>()
+                    .ok()
+                    .map(|addr| addr as u64),
+            };
+        } else if code <= 0 {
+            let pid = reader.read_u32::<LittleEndian>();
+            let uid = reader.read_u32::<LittleEndian>();
+
+            if let (Ok(pid), Ok(uid)) = (pid, uid) {
+                sender = Some((pid, uid));
+            }
+        }
+
+        Ok(SigInfo {
+            signo,
+            errno,
+            code,
+            addr,
+            sender,
+        })
+    }
+
+    fn signal_name(&self) -> String {
+        match SIGNAL_NAMES.get(self.signo as usize) {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => format!("signal {}", self.signo),
+        }
+    }
+}
+
 impl NoteDesc {
     fn gnu(value: &NoteType, data: Vec<u8>) -> NoteDesc {
         use NoteDesc::*;
@@ -378,7 +739,7 @@ impl NoteDesc {
         };
 
         match value {
-            NoteType::ElfNoteAbi => ElfNoteAbi {
+            NoteType::ElfNoteAbi if data.len() >= 16 => ElfNoteAbi {
                 os: NoteOs::new(asu32(0)),
                 major: asu32(4),
                 minor: asu32(8),
@@ -395,10 +756,41 @@ impl NoteDesc {
     fn core(value: &NoteType, data: Vec<u8>, addrsize: u8) -> Result<NoteDesc> {
         match value {
             NoteType::MappedFiles => Ok(NoteDesc::MappedFiles(MappedFiles::new(data, addrsize)?)),
+            // NOTE: only 64-bit layout is supported
+            NoteType::PrStatus if addrsize == 8 => Ok(NoteDesc::PrStatus(PrStatus::new(data)?)),
+            NoteType::SigInfo => Ok(NoteDesc::SigInfo(SigInfo::new(data, addrsize)?)),
             _ => Ok(NoteDesc::Unknown(data)),
         }
     }
 
+    // Notes of FreeBSD, NetBSD, OpenBSD, Go and Android
+    fn os(value: &NoteType, data: Vec<u8>) -> NoteDesc {
+        use NoteDesc::*;
+
+        let version = match data.get(0..4) {
+            Some(bytes) => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            None => return Unknown(data),
+        };
+
+        match value {
+            NoteType::FreeBsdAbiTag => FreeBsdAbiTag(version),
+            NoteType::NetBsdIdent => NetBsdIdent(version),
+            NoteType::OpenBsdIdent => OpenBsdIdent(version),
+            NoteType::AndroidIdent => AndroidIdent(version),
+            NoteType::GoBuildId => GoBuildId(String::from_utf8_lossy(&data).into_owned()),
+            _ => Unknown(data),
+        }
+    }
+
+    // freedesktop.org notes
+    fn fdo(value: &NoteType, data: Vec<u8>) -> NoteDesc {
+        match value {
+            // the JSON is null terminated and padded
+            NoteType::FdoPackagingMetadata => NoteDesc::FdoPackagingMetadata(cstr(&data)),
+            _ => NoteDesc::Unknown(data),
+        }
+    }
+
     fn default(data: Vec<u8>) -> NoteDesc {
         NoteDesc::Unknown(data)
     }
@@ -425,17 +817,28 @@ impl NoteSection {
         size: u64,
         align: u64,
         name: Option<String>,
-        mut reader: &mut Reader,
+        reader: &mut Reader,
     ) -> Result<NoteSection> {
-        reader.seek(SeekFrom::Start(offset))?;
+        // read only notes present in the file, the notes are parsed from
+        // a copy of the section so they can't extend past its end
+        let available = reader.available(offset, size);
+        let truncated = available < size;
+
+        reader.check_range(offset, available)?;
+        let start = offset as usize;
+        let mut section = Reader::new(reader.get_ref()[start..start + available as usize].to_vec());
 
         let mut data = vec![];
         let mut pos: u64 = 0;
 
-        while pos < size {
-            reader.seek(SeekFrom::Start(offset + pos))?;
+        while pos < available {
+            section.seek(SeekFrom::Start(pos))?;
 
-            let note = Note::new(addrsize, align, &mut reader)?;
+            let note = match Note::new(addrsize, align, &mut section) {
+                Ok(note) => note,
+                Err(err) if err.is_eof() && truncated => break,
+                Err(err) => return Err(err),
+            };
             pos += note_next_offset(note.name_size.into(), note.desc_size.into(), align);
 
             // last entry
@@ -448,19 +851,44 @@ impl NoteSection {
 
         Ok(NoteSection {
             data,
-            name: name.unwrap_or_else(|| "".to_string()),
+            name: name.unwrap_or_default(),
+            truncated,
         })
     }
 
-    pub fn new_from_core(addrsize: u8, header: &ProgramHeader, reader: &mut Reader) -> Result<NoteSection> {
-        Ok(NoteSection::new_from_file(
+    pub fn new_from_segment(
+        addrsize: u8,
+        header: &ProgramHeader,
+        reader: &mut Reader,
+    ) -> Result<NoteSection> {
+        NoteSection::new_from_file(
             addrsize,
             header.p_offset,
             header.p_filesz,
             header.p_align,
             Some("Note program header".into()),
             reader,
-        )?)
+        )
+    }
+
+    pub fn new_from_core(
+        addrsize: u8,
+        header: &ProgramHeader,
+        reader: &mut Reader,
+    ) -> Result<NoteSection> {
+        /* The kernel pads names and descriptors of core dump notes
+         * to 4 bytes even in 64-bit files, p_align of the segment
+         * is 0 or 4 on some systems and 8 on others, so it can't be
+         * used to compute the offsets.
+         */
+        NoteSection::new_from_file(
+            addrsize,
+            header.p_offset,
+            header.p_filesz,
+            CORE_NOTE_ALIGN,
+            Some("Note program header".into()),
+            reader,
+        )
     }
 
     pub fn new(
@@ -469,20 +897,21 @@ impl NoteSection {
         name: String,
         reader: &mut Reader,
     ) -> Result<NoteSection> {
-        Ok(NoteSection::new_from_file(
+        NoteSection::new_from_file(
             addrsize,
             header.sh_offset,
             header.sh_size,
             header.sh_addralign,
             Some(name),
             reader,
-        )?)
+        )
     }
 }
 
 impl NoteSections {
     pub fn new(
         addrsize: u8,
+        file_type: &ObjectType,
         headers: &SectionHeaders,
         prheaders: &ProgramHeaders,
         reader: &mut Reader,
@@ -491,18 +920,87 @@ impl NoteSections {
 
         for header in &headers.get_all(SectionHeaderType::Note) {
             let name = headers.strtab.get(header.sh_name as u64);
-            data.push(NoteSection::new(addrsize, &header, name, reader)?);
+            data.push(NoteSection::new(addrsize, header, name, reader)?);
         }
 
         // try to parse notes from program headers
         if data.is_empty() {
             for prheader in &prheaders.get_all(SegmentType::Note) {
-                data.push(NoteSection::new_from_core(addrsize, &prheader, reader)?);
+                let section = match file_type {
+                    ObjectType::CoreFile => NoteSection::new_from_core(addrsize, prheader, reader)?,
+                    _ => NoteSection::new_from_segment(addrsize, prheader, reader)?,
+                };
+                data.push(section);
             }
         }
 
         Ok(NoteSections { data })
     }
+
+    pub fn is_truncated(&self) -> bool {
+        self.data.iter().any(|section| section.truncated)
+    }
+
+    // Number of notes in all sections
+    pub fn count(&self) -> usize {
+        self.data.iter().map(|section| section.data.len()).sum()
+    }
+
+    fn gnu_property(&self, pr_type: u32) -> Option<u32> {
+        for section in &self.data {
+            for note in &section.data {
+                if let NoteDesc::GnuProperty(data) = &note.desc {
+                    if let Some(value) = find_property(data, pr_type) {
+                        return Some(value);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    // Build ID of the file, works also for core dumps and files without
+    // section headers because the notes are then read from PT_NOTE segments
+    pub fn build_id(&self) -> Option<String> {
+        for section in &self.data {
+            for note in &section.data {
+                // use the compact form readelf and debuginfod use
+                if let NoteDesc::GnuBuildID(id) = &note.desc {
+                    return Some(id.replace(' ', "").to_lowercase());
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl FeatureProperties {
+    pub fn new(machine: u16, notes: &NoteSections) -> FeatureProperties {
+        let features = match machine {
+            3 | 62 => notes.gnu_property(GNU_PROPERTY_X86_FEATURE_1_AND),
+            183 => notes.gnu_property(GNU_PROPERTY_AARCH64_FEATURE_1_AND),
+            _ => None,
+        };
+
+        FeatureProperties { machine, features }
+    }
+}
+
+impl fmt::Display for FeatureProperties {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = match self.machine {
+            3 | 62 => ("IBT", "SHSTK"),
+            183 => ("BTI", "PAC"),
+            _ => return Ok(()),
+        };
+
+        let features = self.features.unwrap_or(0);
+        let yes_no = |bit: u32| if features & bit != 0 { "yes" } else { "no" };
+
+        writeln!(f, "{}: {}, {}: {}", names.0, yes_no(1), names.1, yes_no(2))
+    }
 }
 
 impl fmt::Display for NoteSection {
@@ -510,21 +1008,83 @@ impl fmt::Display for NoteSection {
         writeln!(f, "Displaying notes found in: {}", self.name)?;
         writeln!(f, "{:<16} {:<16} {:<32}", "Name", "DescSize", "Desc")?;
 
+        let core = self
+            .data
+            .iter()
+            .any(|note| matches!(note.desc, NoteDesc::PrStatus(_)));
+
+        // in core dumps the process notes are interleaved with the notes
+        // of the first thread, they are shown separately before threads
+        if core {
+            writeln!(f, "Process:")?;
+
+            let process = self
+                .data
+                .iter()
+                .filter(|note| note.note_type.is_process_wide());
+
+            for note in process {
+                note.fmt(f)?;
+            }
+        }
+
+        let mut thread = 0;
+
         for note in &self.data {
-            writeln!(
-                f,
-                "{:<16}  {:#016x} {:<32}",
-                note.name,
-                note.desc_size,
-                format!("{:?}", note.note_type)
-            )?;
-            write!(f, "{}", note.desc)?;
+            if core && note.note_type.is_process_wide() {
+                continue;
+            }
+
+            // each thread of a core dump starts with its prstatus note,
+            // notes describing the thread (registers, siginfo) follow
+            if let NoteDesc::PrStatus(status) = &note.desc {
+                thread += 1;
+                writeln!(f, "Thread {} (LWP {}):", thread, status.pid)?;
+            }
+
+            note.fmt(f)?;
+        }
+
+        if self.truncated {
+            writeln!(f, "file truncated after {} entries", self.data.len())?;
         }
 
         Ok(())
     }
 }
 
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<16}  {:#016x} {:<32}",
+            self.name,
+            self.desc_size,
+            self.note_type.to_string()
+        )?;
+        write!(f, "{}", self.desc)
+    }
+}
+
+// Unknown values are shown as numbers, the others by their names
+impl fmt::Display for NoteType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NoteType::Unknown(value) => write!(f, "Unknown({})", value),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl fmt::Display for NoteOs {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NoteOs::Unknown(value) => write!(f, "Unknown({})", value),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
 impl fmt::Display for NoteDesc {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         use NoteDesc::*;
@@ -536,9 +1096,72 @@ impl fmt::Display for NoteDesc {
                 minor,
                 patch,
             } => {
-                writeln!(f, "  OS: {:?} {}.{}.{}", os, major, minor, patch)?;
+                writeln!(f, "  OS: {} {}.{}.{}", os, major, minor, patch)?;
             }
             GnuBuildID(id) => writeln!(f, "  BuildID: {}", id)?,
+            FreeBsdAbiTag(version) => writeln!(
+                f,
+                "  OS: FreeBSD {}.{} ({})",
+                version / 100000,
+                version / 1000 % 100,
+                version
+            )?,
+            NetBsdIdent(version) => writeln!(
+                f,
+                "  OS: NetBSD {}.{} ({})",
+                version / 100000000,
+                version / 1000000 % 100,
+                version
+            )?,
+            OpenBsdIdent(version) => writeln!(f, "  OS: OpenBSD ({})", version)?,
+            AndroidIdent(version) => writeln!(f, "  Android API level: {}", version)?,
+            GoBuildId(id) => writeln!(f, "  Go build ID: {}", id)?,
+            FdoPackagingMetadata(metadata) => writeln!(f, "  Packaging metadata: {}", metadata)?,
+            PrStatus(status) => {
+                writeln!(
+                    f,
+                    "  Signal: {} PID: {} PPID: {} PGRP: {} SID: {}",
+                    status.signal, status.pid, status.ppid, status.pgrp, status.sid
+                )?;
+
+                for (i, chunk) in status.registers.chunks(3).enumerate() {
+                    write!(f, " ")?;
+
+                    for (j, value) in chunk.iter().enumerate() {
+                        let index = i * 3 + j;
+                        let name = if status.registers.len() == X86_64_REGISTERS.len() {
+                            X86_64_REGISTERS[index].to_string()
+                        } else {
+                            format!("r{}", index)
+                        };
+
+                        write!(f, " {:>8}: {:#018x}", name, value)?;
+                    }
+                    writeln!(f)?;
+                }
+            }
+            SigInfo(info) => {
+                write!(
+                    f,
+                    "  Signal: {} ({}), code: {}",
+                    info.signal_name(),
+                    info.signo,
+                    info.code
+                )?;
+
+                if info.errno != 0 {
+                    write!(f, ", errno: {}", info.errno)?;
+                }
+
+                if let Some(addr) = info.addr {
+                    write!(f, ", fault addr: {:#x}", addr)?;
+                }
+
+                if let Some((pid, uid)) = info.sender {
+                    write!(f, ", sent by PID: {} UID: {}", pid, uid)?;
+                }
+                writeln!(f)?;
+            }
             MappedFiles(files) => {
                 writeln!(f, "  Page size: {}", files.pagesize)?;
                 writeln!(
@@ -560,6 +1183,131 @@ impl fmt::Display for NoteDesc {
     }
 }
 
+impl NoteDesc {
+    // Decoded fields of the descriptor as JSON object fields
+    fn json_fields(&self) -> Vec<(&'static str, String)> {
+        use NoteDesc::*;
+
+        match &self {
+            ElfNoteAbi {
+                os,
+                major,
+                minor,
+                patch,
+            } => vec![
+                ("os", json::string(&os.to_string())),
+                (
+                    "version",
+                    json::string(&format!("{}.{}.{}", major, minor, patch)),
+                ),
+            ],
+            GnuBuildID(id) => vec![(
+                "build_id",
+                json::string(&id.replace(' ', "").to_lowercase()),
+            )],
+            GnuGoldVersion(version) => vec![("version", json::string(version))],
+            FreeBsdAbiTag(version) | NetBsdIdent(version) | OpenBsdIdent(version) => {
+                vec![("version", version.to_string())]
+            }
+            AndroidIdent(version) => vec![("api_level", version.to_string())],
+            GoBuildId(id) => vec![("build_id", json::string(id))],
+            FdoPackagingMetadata(metadata) => vec![("metadata", json::string(metadata))],
+            GnuHwCap(data) | GnuProperty(data) | Unknown(data) => {
+                vec![("data", json::string(&to_hex_string(data.clone())))]
+            }
+            PrStatus(status) => {
+                let registers: Vec<(&str, String)> = status
+                    .registers
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| {
+                        let name = if status.registers.len() == X86_64_REGISTERS.len() {
+                            X86_64_REGISTERS[index]
+                        } else {
+                            ""
+                        };
+                        (name, json::hex(*value))
+                    })
+                    .collect();
+
+                let registers = if registers.iter().all(|(name, _)| !name.is_empty()) {
+                    json::object(&registers)
+                } else {
+                    let values: Vec<String> =
+                        registers.into_iter().map(|(_, value)| value).collect();
+                    json::array(&values)
+                };
+
+                vec![
+                    ("signal", status.signal.to_string()),
+                    ("pid", status.pid.to_string()),
+                    ("ppid", status.ppid.to_string()),
+                    ("pgrp", status.pgrp.to_string()),
+                    ("sid", status.sid.to_string()),
+                    ("registers", registers),
+                ]
+            }
+            MappedFiles(files) => {
+                let mappings: Vec<String> = files
+                    .files
+                    .iter()
+                    .map(|file| {
+                        json::object(&[
+                            ("start", json::hex(file.start)),
+                            ("end", json::hex(file.end)),
+                            ("page_offset", json::hex(file.page_offset)),
+                            ("filename", json::string(&file.filename)),
+                        ])
+                    })
+                    .collect();
+
+                vec![
+                    ("page_size", files.pagesize.to_string()),
+                    ("files", json::array(&mappings)),
+                ]
+            }
+            SigInfo(info) => vec![
+                ("signal", json::string(&info.signal_name())),
+                ("signo", info.signo.to_string()),
+                ("errno", info.errno.to_string()),
+                ("code", info.code.to_string()),
+                ("fault_addr", json::or_null(info.addr.map(json::hex))),
+                (
+                    "sender_pid",
+                    json::or_null(info.sender.map(|(pid, _)| pid.to_string())),
+                ),
+                (
+                    "sender_uid",
+                    json::or_null(info.sender.map(|(_, uid)| uid.to_string())),
+                ),
+            ],
+        }
+    }
+}
+
+impl NoteSections {
+    // Notes of all sections as JSON array, one object per note
+    pub fn to_json(&self) -> String {
+        let mut notes = vec![];
+
+        for section in &self.data {
+            for note in &section.data {
+                let mut fields = vec![
+                    ("section", json::string(&section.name)),
+                    ("owner", json::string(note.name.trim_end_matches('\0'))),
+                    ("type", json::string(&note.note_type.to_string())),
+                    ("size", note.desc_size.to_string()),
+                ];
+
+                fields.extend(note.desc.json_fields());
+                notes.push(json::object(&fields));
+            }
+        }
+
+        json::array(&notes)
+    }
+}
+
 impl fmt::Display for NoteSections {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for section in &self.data {
@@ -568,3 +1316,209 @@ impl fmt::Display for NoteSections {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, strip, ElfBuilder, Section, Segment};
+
+    // SHT_NOTE, PT_NOTE
+    const NOTE: u32 = 7;
+    const PT_NOTE: u32 = 4;
+
+    // Note with the name and descriptor padded to `align' bytes
+    fn note(name: &str, note_type: u32, desc: &[u8], align: usize) -> Vec<u8> {
+        let mut data = vec![];
+
+        data.extend(&(name.len() as u32 + 1).to_le_bytes());
+        data.extend(&(desc.len() as u32).to_le_bytes());
+        data.extend(&note_type.to_le_bytes());
+        data.extend(name.as_bytes());
+        data.push(0);
+        data.resize(data.len().next_multiple_of(align), 0);
+        data.extend(desc);
+        data.resize(data.len().next_multiple_of(align), 0);
+
+        data
+    }
+
+    fn build_id_note(id: &[u8]) -> Vec<u8> {
+        // NT_GNU_BUILD_ID
+        note("GNU", 3, id, 4)
+    }
+
+    fn read_section(section: Section) -> Result<NoteSection> {
+        // DYN, x86-64
+        let (header, mut reader) = parse(ElfBuilder::new(3, 62).section(section).build());
+        let sections = SectionHeaders::new(&header, &mut reader)?;
+        let header = sections.get_by_name(".note.gnu.build-id").unwrap();
+
+        NoteSection::new(8, &header, ".note.gnu.build-id".into(), &mut reader)
+    }
+
+    #[test]
+    fn reads_section() {
+        let data = build_id_note(&[0xde, 0xad, 0xbe, 0xef]);
+        let section = read_section(Section::new(".note.gnu.build-id", NOTE, data)).unwrap();
+
+        assert_eq!(section.data.len(), 1);
+        assert!(!section.truncated);
+    }
+
+    #[test]
+    fn short_abi_tag() {
+        // NT_GNU_ABI_TAG with only the OS and the major version
+        let data = note("GNU", 1, &[0, 0, 0, 0, 3, 0, 0, 0], 4);
+        let section = read_section(Section::new(".note.gnu.build-id", NOTE, data)).unwrap();
+
+        assert!(matches!(section.data[0].desc, NoteDesc::Unknown(_)));
+    }
+
+    fn build_id(data: Vec<u8>) -> Option<String> {
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let segments = ProgramHeaders::new(&header, &mut reader).unwrap();
+
+        NoteSections::new(8, &header.e_type, &sections, &segments, &mut reader)
+            .unwrap()
+            .build_id()
+    }
+
+    #[test]
+    fn build_id_from_section() {
+        let data = build_id_note(&[0xde, 0xad, 0xbe, 0xef]);
+        // DYN, x86-64
+        let data = ElfBuilder::new(3, 62)
+            .section(Section::new(".note.gnu.build-id", NOTE, data))
+            .build();
+
+        assert_eq!(build_id(data), Some("deadbeef".to_string()));
+    }
+
+    #[test]
+    fn build_id_from_segment() {
+        let data = build_id_note(&[0xca, 0xfe]);
+        // DYN, x86-64, without section headers
+        let data = ElfBuilder::new(3, 62)
+            .segment(Segment::new(PT_NOTE, data))
+            .build();
+
+        assert_eq!(build_id(strip(data)), Some("cafe".to_string()));
+    }
+
+    #[test]
+    fn note_past_section_end() {
+        // the descriptor is in the file, but not in the section
+        let mut section = Section::new(".note.gnu.build-id", NOTE, build_id_note(&[0; 20]));
+        section.sh_size = Some(16);
+
+        assert!(read_section(section).is_err());
+    }
+
+    #[test]
+    fn reads_core_notes() {
+        // descriptors padded to 4 bytes, the segment is aligned to 8
+        let mut data = note("CORE", 3, &[1; 12], 4);
+        data.extend(note("CORE", 6, &[2; 4], 4));
+        data.extend(note("LINUX", 0x202, &[3; 20], 4));
+
+        // CORE, x86-64
+        let data = ElfBuilder::new(4, 62)
+            .segment(Segment::new(PT_NOTE, data))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let segments = ProgramHeaders::new(&header, &mut reader).unwrap();
+        let notes =
+            NoteSections::new(8, &ObjectType::CoreFile, &sections, &segments, &mut reader).unwrap();
+
+        let notes = &notes.data[0].data;
+        assert_eq!(notes.len(), 3);
+        assert!(matches!(notes[0].note_type, NoteType::PrPsInfo));
+        assert!(matches!(notes[1].note_type, NoteType::Auxw));
+        assert!(matches!(notes[2].note_type, NoteType::X86ExtendedState));
+        assert!(matches!(&notes[2].desc, NoteDesc::Unknown(desc) if desc == &[3; 20]));
+    }
+
+    #[test]
+    fn groups_core_notes() {
+        // NT_PRSTATUS with just the pid and no registers
+        let prstatus = |pid: u32| {
+            let mut desc = vec![0; PRSTATUS_REGS_OFFSET + 8];
+            desc[32..36].copy_from_slice(&pid.to_le_bytes());
+            note("CORE", 1, &desc, 4)
+        };
+
+        // the process notes follow the prstatus of the first thread
+        let mut data = prstatus(100);
+        data.extend(note("CORE", 3, &[1; 12], 4));
+        data.extend(note("CORE", 6, &[2; 16], 4));
+        data.extend(note("CORE", 2, &[3; 8], 4));
+        data.extend(prstatus(101));
+
+        // CORE, x86-64
+        let data = ElfBuilder::new(4, 62)
+            .segment(Segment::new(PT_NOTE, data))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let segments = ProgramHeaders::new(&header, &mut reader).unwrap();
+        let notes =
+            NoteSections::new(8, &ObjectType::CoreFile, &sections, &segments, &mut reader).unwrap();
+
+        // headings and note types, without the descriptors
+        let output = notes.to_string();
+        let lines: Vec<&str> = output
+            .lines()
+            .skip(2)
+            .filter(|line| !line.starts_with(' '))
+            .map(|line| match line.strip_prefix("CORE") {
+                Some(row) => row.split_whitespace().last().unwrap(),
+                None => line,
+            })
+            .collect();
+
+        assert_eq!(
+            lines,
+            [
+                "Process:",
+                "PrPsInfo",
+                "Auxw",
+                "Thread 1 (LWP 100):",
+                "PrStatus",
+                "PrFpReg",
+                "Thread 2 (LWP 101):",
+                "PrStatus",
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_sigsegv() {
+        // SIGSEGV, errno 0, SEGV_MAPERR and the fault address in the union
+        let mut desc = vec![0; 128];
+        desc[0..4].copy_from_slice(&11i32.to_le_bytes());
+        desc[8..12].copy_from_slice(&1i32.to_le_bytes());
+        desc[16..24].copy_from_slice(&0xdeadbeefu64.to_le_bytes());
+
+        // CORE, x86-64, NT_SIGINFO
+        let data = ElfBuilder::new(4, 62)
+            .segment(Segment::new(PT_NOTE, note("CORE", 0x53494749, &desc, 4)))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let segments = ProgramHeaders::new(&header, &mut reader).unwrap();
+        let notes =
+            NoteSections::new(8, &ObjectType::CoreFile, &sections, &segments, &mut reader).unwrap();
+
+        let note = &notes.data[0].data[0];
+        assert!(matches!(&note.desc, NoteDesc::SigInfo(info) if info.addr == Some(0xdeadbeef)));
+        assert_eq!(
+            note.desc.to_string(),
+            "  Signal: SIGSEGV (11), code: 1, fault addr: 0xdeadbeef\n"
+        );
+    }
+}
@@ -1,7 +1,38 @@
+use crate::error::Result;
 use crate::file::ElfFileHeader;
-use crate::reader::{LittleEndian, ReadBytesExt, Reader, Seek};
+use crate::json;
+use crate::reader::{LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
 use std::fmt;
 
+// Size of the ELF64 program header
+const PHDR_SIZE: u64 = 56;
+
+// Segment is executable
+const PF_X: u32 = 1 << 0;
+// Segment is writable
+const PF_W: u32 = 1 << 1;
+// Segment is readable
+const PF_R: u32 = 1 << 2;
+
+// Flags in the order used by readelf, e.g. `R E' or `RW '
+fn p_flags(value: u32) -> String {
+    let mut flags = String::new();
+
+    let mut matchflag = |flag: u32, ch: char| {
+        if value & flag == flag {
+            flags.push(ch);
+        } else {
+            flags.push(' ');
+        }
+    };
+
+    matchflag(PF_R, 'R');
+    matchflag(PF_W, 'W');
+    matchflag(PF_X, 'E');
+
+    flags
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum SegmentType {
     // Program header table entry unused
@@ -26,6 +57,8 @@ pub enum SegmentType {
     GnuStack,
     // Read-only after relocation
     GnuRelRo,
+    // GNU property note (.note.gnu.property)
+    GnuProperty,
     // Unknown
     Unknown(u32),
 }
@@ -55,6 +88,22 @@ pub struct ProgramHeaders {
     pub headers: Vec<ProgramHeader>,
 }
 
+// Audit of loadable segments that are both writable and executable
+#[derive(Debug)]
+pub struct SegmentPermissions {
+    // Index, type and flags of W+X segments
+    writable_executable: Vec<(usize, SegmentType, u32)>,
+    // Flags of the GNU_STACK segment, if present
+    stack: Option<u32>,
+}
+
+// Virtual address map of loadable segments
+#[derive(Debug)]
+pub struct SegmentLayout {
+    // Index, [start, end) range and flags of segments sorted by start
+    ranges: Vec<(usize, u64, u64, u32)>,
+}
+
 impl SegmentType {
     fn new(value: u32) -> SegmentType {
         use SegmentType::*;
@@ -71,23 +120,24 @@ impl SegmentType {
             0x6474e550 => GnuEhFrame,
             0x6474e551 => GnuStack,
             0x6474e552 => GnuRelRo,
+            0x6474e553 => GnuProperty,
             _ => Unknown(value),
         }
     }
 }
 
 impl ProgramHeader {
-    fn new(reader: &mut Reader) -> ProgramHeader {
-        ProgramHeader {
-            p_type: SegmentType::new(reader.read_u32::<LittleEndian>().unwrap()),
-            p_flags: reader.read_u32::<LittleEndian>().unwrap(),
-            p_offset: reader.read_u64::<LittleEndian>().unwrap(),
-            p_vaddr: reader.read_u64::<LittleEndian>().unwrap(),
-            p_paddr: reader.read_u64::<LittleEndian>().unwrap(),
-            p_filesz: reader.read_u64::<LittleEndian>().unwrap(),
-            p_memsiz: reader.read_u64::<LittleEndian>().unwrap(),
-            p_align: reader.read_u64::<LittleEndian>().unwrap(),
-        }
+    fn new(reader: &mut Reader) -> Result<ProgramHeader> {
+        Ok(ProgramHeader {
+            p_type: SegmentType::new(reader.read_u32::<LittleEndian>()?),
+            p_flags: reader.read_u32::<LittleEndian>()?,
+            p_offset: reader.read_u64::<LittleEndian>()?,
+            p_vaddr: reader.read_u64::<LittleEndian>()?,
+            p_paddr: reader.read_u64::<LittleEndian>()?,
+            p_filesz: reader.read_u64::<LittleEndian>()?,
+            p_memsiz: reader.read_u64::<LittleEndian>()?,
+            p_align: reader.read_u64::<LittleEndian>()?,
+        })
     }
 }
 
@@ -104,27 +154,106 @@ impl ProgramHeaders {
         headers
     }
 
-    pub fn new(header: &ElfFileHeader, mut reader: &mut Reader) -> ProgramHeaders {
-        reader
-            .seek(std::io::SeekFrom::Start(header.e_phoff))
-            .unwrap();
+    // Translate virtual address to file offset using the loadable segments
+    pub fn vaddr_to_offset(&self, vaddr: u64) -> Option<u64> {
+        self.headers
+            .iter()
+            .filter(|header| header.p_type == SegmentType::Load)
+            .find(|header| vaddr >= header.p_vaddr && vaddr - header.p_vaddr < header.p_filesz)
+            .map(|header| header.p_offset + (vaddr - header.p_vaddr))
+    }
+
+    pub fn to_json(&self) -> String {
+        let headers: Vec<String> = self
+            .headers
+            .iter()
+            .map(|header| {
+                json::object(&[
+                    ("type", json::string(&header.p_type.to_string())),
+                    (
+                        "flags",
+                        json::string(&p_flags(header.p_flags).replace(' ', "")),
+                    ),
+                    ("offset", json::hex(header.p_offset)),
+                    ("vaddr", json::hex(header.p_vaddr)),
+                    ("paddr", json::hex(header.p_paddr)),
+                    ("filesz", json::hex(header.p_filesz)),
+                    ("memsz", json::hex(header.p_memsiz)),
+                    ("align", json::hex(header.p_align)),
+                ])
+            })
+            .collect();
 
+        json::array(&headers)
+    }
+
+    pub fn new(header: &ElfFileHeader, reader: &mut Reader) -> Result<ProgramHeaders> {
         let mut headers: Vec<ProgramHeader> = vec![];
-        let mut section_no: u16 = 0;
 
-        while section_no < header.e_phnum {
-            headers.push(ProgramHeader::new(&mut reader));
-            section_no += 1;
+        // read only headers present in the file
+        let size = reader.available(header.e_phoff, header.e_phnum as u64 * PHDR_SIZE);
+
+        reader.seek(SeekFrom::Start(header.e_phoff))?;
+
+        for _ in 0..size / PHDR_SIZE {
+            headers.push(ProgramHeader::new(reader)?);
+        }
+
+        Ok(ProgramHeaders { headers })
+    }
+}
+
+impl SegmentPermissions {
+    pub fn new(headers: &ProgramHeaders) -> SegmentPermissions {
+        let mut writable_executable = vec![];
+
+        for (i, header) in headers.headers.iter().enumerate() {
+            if header.p_type == SegmentType::Load && header.p_flags & (PF_W | PF_X) == PF_W | PF_X {
+                writable_executable.push((i, header.p_type.clone(), header.p_flags));
+            }
+        }
+
+        let stack = headers
+            .get_all(SegmentType::GnuStack)
+            .pop()
+            .map(|header| header.p_flags);
+
+        SegmentPermissions {
+            writable_executable,
+            stack,
         }
+    }
+
+    // There are no W+X segments and the stack is known to be non-executable
+    pub fn is_ok(&self) -> bool {
+        self.writable_executable.is_empty()
+            && matches!(self.stack, Some(flags) if flags & PF_X == 0)
+    }
+}
 
-        ProgramHeaders { headers }
+impl SegmentLayout {
+    pub fn new(headers: &ProgramHeaders) -> SegmentLayout {
+        let mut ranges: Vec<(usize, u64, u64, u32)> = headers
+            .headers
+            .iter()
+            .enumerate()
+            .filter(|(_, header)| header.p_type == SegmentType::Load)
+            .map(|(i, header)| {
+                let end = header.p_vaddr.saturating_add(header.p_memsiz);
+                (i, header.p_vaddr, end, header.p_flags)
+            })
+            .collect();
+
+        ranges.sort_by_key(|(_, start, _, _)| *start);
+
+        SegmentLayout { ranges }
     }
 }
 
 impl fmt::Display for ProgramHeader {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // NOTE: we have to use `format!` because Debug ignores padding
-        write!(f, "{:16}", format!("{:?}", self.p_type))?;
+        write!(f, "{:16}", self.p_type.to_string())?;
         write!(f, "{:#016x} ", self.p_offset)?;
         write!(f, "{:#016x} ", self.p_vaddr)?;
         writeln!(f, "{:#016x} ", self.p_paddr)?;
@@ -132,22 +261,7 @@ impl fmt::Display for ProgramHeader {
         write!(f, "{:16}{:#016x} ", "", self.p_filesz)?;
         write!(f, "{:#016x} ", self.p_memsiz)?;
 
-        let mut flags = String::new();
-
-        let mut matchflag = |flag: u32, ch: char| {
-            if self.p_flags & flag == flag {
-                flags.push(ch);
-            } else {
-                flags.push(' ');
-            }
-            flags.push(' ');
-        };
-
-        matchflag(1 << 0, 'X');
-        matchflag(1 << 1, 'W');
-        matchflag(1 << 2, 'R');
-
-        write!(f, "{}  ", flags)?;
+        write!(f, "{:<6}  ", p_flags(self.p_flags))?;
         writeln!(f, "{:#08x}", self.p_align)
     }
 }
@@ -166,12 +280,131 @@ impl fmt::Display for ProgramHeaders {
             "", "FileSiz", "MemSiz", "Flags", "Align"
         )?;
 
-        let mut result: fmt::Result = Ok(());
-
         for header in &self.headers {
-            result = header.fmt(f);
+            header.fmt(f)?;
+
+            // the same data are covered by a Note segment too
+            if header.p_type == SegmentType::GnuProperty {
+                writeln!(f, "{:16}[Holds the .note.gnu.property note]", "")?;
+            }
         }
 
-        result
+        let loads = self.get_all(SegmentType::Load);
+
+        if loads.is_empty() {
+            return Ok(());
+        }
+
+        let size = loads
+            .iter()
+            .fold(0u64, |size, header| size.saturating_add(header.p_memsiz));
+        let end = loads
+            .iter()
+            .map(|header| header.p_vaddr.saturating_add(header.p_memsiz))
+            .max()
+            .unwrap_or(0);
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{} LOAD segments, total memory size {:#x}, highest address {:#x}",
+            loads.len(),
+            size,
+            end
+        )
+    }
+}
+
+impl fmt::Display for SegmentType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SegmentType::Unknown(value) => write!(f, "Unknown({:#x})", value),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl fmt::Display for SegmentLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Memory layout of loadable segments:")?;
+
+        let mut previous: Option<u64> = None;
+
+        for (i, start, end, flags) in &self.ranges {
+            match previous {
+                Some(prev) if prev < *start => writeln!(
+                    f,
+                    "{:#018x}-{:#018x}     gap of {:#x} bytes",
+                    prev,
+                    start,
+                    start - prev
+                )?,
+                Some(prev) if prev > *start => writeln!(
+                    f,
+                    "{:#018x}-{:#018x}     overlap of {:#x} bytes",
+                    start,
+                    prev,
+                    prev - start
+                )?,
+                _ => {}
+            }
+
+            writeln!(
+                f,
+                "{:#018x}-{:#018x} {} segment [{:02}]",
+                start,
+                end,
+                p_flags(*flags),
+                i
+            )?;
+
+            previous = Some(previous.map_or(*end, |prev| prev.max(*end)));
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for SegmentPermissions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Segment permissions:")?;
+
+        if self.writable_executable.is_empty() {
+            writeln!(f, "No writable and executable segments")?;
+        }
+
+        for (i, kind, flags) in &self.writable_executable {
+            writeln!(
+                f,
+                "Warning: segment [{:02}] {} is writable and executable ({})",
+                i,
+                kind,
+                p_flags(*flags).trim_end()
+            )?;
+        }
+
+        match self.stack {
+            Some(flags) if flags & PF_X == PF_X => writeln!(f, "Warning: stack is executable"),
+            Some(_) => writeln!(f, "Stack is not executable"),
+            None => writeln!(
+                f,
+                "Warning: GnuStack segment is missing, stack may be executable"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flag_strings() {
+        assert_eq!(p_flags(PF_R | PF_X), "R E");
+        assert_eq!(p_flags(PF_R | PF_W), "RW ");
+        assert_eq!(p_flags(PF_R | PF_W | PF_X), "RWE");
+        assert_eq!(p_flags(0), "   ");
+        // unknown bits are not shown
+        assert_eq!(p_flags(0x0ff0_0000 | PF_R), "R  ");
     }
 }
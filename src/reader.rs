@@ -1,5 +1,309 @@
+use crate::error::ElfError;
 pub use byteorder::{LittleEndian, ReadBytesExt};
 pub use std::io::prelude::*;
 pub use std::io::{Cursor, SeekFrom};
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
 
 pub type Reader = Cursor<Vec<u8>>;
+
+// LEB128 encoded 64-bit number occupies at most 10 bytes
+const LEB128_MAX_SIZE: u32 = 10;
+
+// Longest string read from the file when its terminator is missing
+const CSTR_MAX_SIZE: usize = 4096;
+
+fn leb128_overflow() -> Error {
+    Error::new(ErrorKind::InvalidData, "LEB128 number overflows 64 bits")
+}
+
+// Window of the input data treated as the whole file, e.g. one of the
+// images stored back-to-back in a memory dump
+#[derive(Debug, Clone, Copy)]
+pub struct InputRange {
+    pub start: u64,
+    pub end: u64,
+}
+
+impl InputRange {
+    // Reader over the window only, reads and range checks use the window
+    // length, so anything outside of it fails as if the file ended there
+    pub fn reader(&self, mut data: Vec<u8>) -> Result<Reader, ElfError> {
+        let len = data.len() as u64;
+
+        if self.end > len {
+            return Err(ElfError::Truncated {
+                offset: self.start,
+                size: self.end - self.start,
+                len,
+            });
+        }
+
+        data.truncate(self.end as usize);
+        data.drain(..self.start as usize);
+
+        Ok(Reader::new(data))
+    }
+}
+
+// String up to the first null byte or the end of `data`, invalid UTF-8
+// sequences are replaced
+pub fn cstr(data: &[u8]) -> String {
+    let end = data
+        .iter()
+        .position(|byte| *byte == 0)
+        .unwrap_or(data.len());
+
+    String::from_utf8_lossy(&data[..end]).into_owned()
+}
+
+fn parse_number(src: &str) -> Result<u64, String> {
+    let result = match src.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => src.parse(),
+    };
+
+    result.map_err(|err| format!("invalid number {}: {}", src, err))
+}
+
+impl FromStr for InputRange {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<InputRange, String> {
+        let (start, end) = match value.find(':') {
+            Some(pos) => (&value[..pos], &value[pos + 1..]),
+            None => return Err(format!("invalid range: {}, expected START:END", value)),
+        };
+        let start = parse_number(start)?;
+        let end = parse_number(end)?;
+
+        if start > end {
+            return Err(format!("invalid range: {}, start is past the end", value));
+        }
+
+        Ok(InputRange { start, end })
+    }
+}
+
+pub trait ReaderExt {
+    // Total length of the underlying data
+    fn len(&self) -> u64;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // Check that structure of `size` bytes at `offset` fits into the data
+    fn check_range(&self, offset: u64, size: u64) -> Result<(), ElfError> {
+        match offset.checked_add(size) {
+            Some(end) if end <= self.len() => Ok(()),
+            _ => Err(ElfError::Truncated {
+                offset,
+                size,
+                len: self.len(),
+            }),
+        }
+    }
+
+    // Number of bytes of `size` bytes at `offset` present in the data
+    fn available(&self, offset: u64, size: u64) -> u64 {
+        self.len().saturating_sub(offset).min(size)
+    }
+
+    // Read unsigned LEB128 encoded number
+    fn read_uleb128(&mut self) -> Result<u64, Error>;
+
+    // Read signed LEB128 encoded number
+    fn read_sleb128(&mut self) -> Result<i64, Error>;
+
+    // Read null terminated string at `offset` and move past its terminator,
+    // unterminated strings end at the end of the data or after
+    // CSTR_MAX_SIZE bytes
+    fn read_cstr_at(&mut self, offset: u64) -> Result<String, ElfError>;
+
+    // Same as `read_cstr_at', but the string must lie within `size` bytes
+    // at `offset', e.g. in a segment
+    fn read_cstr_in(&mut self, offset: u64, size: u64) -> Result<String, ElfError>;
+}
+
+impl ReaderExt for Reader {
+    fn len(&self) -> u64 {
+        self.get_ref().len() as u64
+    }
+
+    fn read_cstr_at(&mut self, offset: u64) -> Result<String, ElfError> {
+        self.read_cstr_in(offset, self.len().saturating_sub(offset))
+    }
+
+    fn read_cstr_in(&mut self, offset: u64, size: u64) -> Result<String, ElfError> {
+        self.check_range(offset, size)?;
+
+        let data = &self.get_ref()[offset as usize..(offset + size) as usize];
+        let data = &data[..data.len().min(CSTR_MAX_SIZE)];
+
+        // skip the terminator, if there is one
+        let size = match data.iter().position(|byte| *byte == 0) {
+            Some(end) => end + 1,
+            None => data.len(),
+        };
+        let result = cstr(data);
+
+        self.set_position(offset + size as u64);
+
+        Ok(result)
+    }
+
+    fn read_uleb128(&mut self) -> Result<u64, Error> {
+        let mut result: u64 = 0;
+        let mut shift = 0;
+
+        for _ in 0..LEB128_MAX_SIZE {
+            let byte = self.read_u8()?;
+            let value = (byte & 0x7f) as u64;
+
+            // the last byte can hold only the highest bit
+            if shift == 63 && value > 1 {
+                return Err(leb128_overflow());
+            }
+
+            result |= value << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+
+        Err(leb128_overflow())
+    }
+
+    fn read_sleb128(&mut self) -> Result<i64, Error> {
+        let mut result: i64 = 0;
+        let mut shift = 0;
+
+        for _ in 0..LEB128_MAX_SIZE {
+            let byte = self.read_u8()?;
+            let value = (byte & 0x7f) as i64;
+
+            // the last byte holds only the sign, it has to be
+            // either all ones or all zeros
+            if shift == 63 && value != 0 && value != 0x7f {
+                return Err(leb128_overflow());
+            }
+
+            result |= value << shift;
+            shift += 7;
+
+            if byte & 0x80 == 0 {
+                // sign extend
+                if shift < 64 && byte & 0x40 != 0 {
+                    result |= -1 << shift;
+                }
+                return Ok(result);
+            }
+        }
+
+        Err(leb128_overflow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_cstr() {
+        let mut reader = Reader::new(b"\0abc\0def".to_vec());
+
+        assert_eq!(reader.read_cstr_at(1).unwrap(), "abc");
+        assert_eq!(reader.position(), 5);
+    }
+
+    #[test]
+    fn reads_empty_cstr() {
+        let mut reader = Reader::new(b"\0abc\0".to_vec());
+
+        assert_eq!(reader.read_cstr_at(0).unwrap(), "");
+        assert_eq!(reader.position(), 1);
+        assert_eq!(reader.read_cstr_at(5).unwrap(), "");
+        assert!(reader.read_cstr_at(6).is_err());
+    }
+
+    #[test]
+    fn reads_unterminated_cstr() {
+        let mut reader = Reader::new(b"\0abc".to_vec());
+        assert_eq!(reader.read_cstr_at(1).unwrap(), "abc");
+
+        let mut reader = Reader::new(vec![b'a'; CSTR_MAX_SIZE * 2]);
+        assert_eq!(reader.read_cstr_at(0).unwrap().len(), CSTR_MAX_SIZE);
+    }
+
+    #[test]
+    fn reads_cstr_in_range() {
+        let mut reader = Reader::new(b"abcdef\0".to_vec());
+
+        assert_eq!(reader.read_cstr_in(1, 3).unwrap(), "bcd");
+        assert_eq!(reader.position(), 4);
+        assert!(reader.read_cstr_in(4, 4).is_err());
+    }
+
+    fn uleb128(data: &[u8]) -> Result<u64, Error> {
+        Reader::new(data.to_vec()).read_uleb128()
+    }
+
+    fn sleb128(data: &[u8]) -> Result<i64, Error> {
+        Reader::new(data.to_vec()).read_sleb128()
+    }
+
+    #[test]
+    fn reads_uleb128() {
+        assert_eq!(uleb128(&[0x02]).unwrap(), 2);
+        assert_eq!(uleb128(&[0xe5, 0x8e, 0x26]).unwrap(), 624485);
+        // redundant padding is allowed
+        assert_eq!(uleb128(&[0x80, 0x80, 0x00]).unwrap(), 0);
+
+        let mut max = vec![0xff; 9];
+        max.push(0x01);
+        assert_eq!(uleb128(&max).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn uleb128_overflow() {
+        let mut data = vec![0xff; 9];
+        data.push(0x02);
+        assert_eq!(uleb128(&data).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        // more than 10 bytes
+        let mut data = vec![0x80; 10];
+        data.push(0x00);
+        assert_eq!(uleb128(&data).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        assert_eq!(
+            uleb128(&[0x80]).unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+    }
+
+    #[test]
+    fn reads_sleb128() {
+        assert_eq!(sleb128(&[0x02]).unwrap(), 2);
+        assert_eq!(sleb128(&[0x7f]).unwrap(), -1);
+        assert_eq!(sleb128(&[0xc0, 0xbb, 0x78]).unwrap(), -123456);
+
+        let mut min = vec![0x80; 9];
+        min.push(0x7f);
+        assert_eq!(sleb128(&min).unwrap(), i64::MIN);
+    }
+
+    #[test]
+    fn sleb128_overflow() {
+        let mut data = vec![0x80; 9];
+        data.push(0x01);
+        assert_eq!(sleb128(&data).unwrap_err().kind(), ErrorKind::InvalidData);
+
+        assert_eq!(
+            sleb128(&[0xff]).unwrap_err().kind(),
+            ErrorKind::UnexpectedEof
+        );
+    }
+}
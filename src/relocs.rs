@@ -1,8 +1,17 @@
-use crate::reader::{LittleEndian, ReadBytesExt, Reader, Seek, SeekFrom};
+use crate::error::{ElfError, Result};
+use crate::json;
+use crate::program::{ProgramHeaders, SegmentType};
+use crate::reader::{LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
 use crate::section::{SectionHeader, SectionHeaderType, SectionHeaders};
-use crate::symbols::SymbolTable;
+use crate::symbols::{truncated_name, SymbolTable};
+use crate::version::{symbol_versions, VersionSection};
+use std::borrow::Cow;
 use std::fmt;
 
+// Size of the ELF64 relocation entries without and with addend
+const REL_SIZE: u64 = 16;
+const RELA_SIZE: u64 = 24;
+
 fn amd64_relocs(value: u32) -> &'static str {
     match value {
         /* No reloc */
@@ -91,6 +100,381 @@ fn amd64_relocs(value: u32) -> &'static str {
     }
 }
 
+fn mips_relocs(value: u32) -> &'static str {
+    match value {
+        /* No reloc */
+        0 => "R_MIPS_NONE",
+        /* Direct 16 bit */
+        1 => "R_MIPS_16",
+        /* Direct 32 bit */
+        2 => "R_MIPS_32",
+        /* PC relative 32 bit */
+        3 => "R_MIPS_REL32",
+        /* Direct 26 bit shifted */
+        4 => "R_MIPS_26",
+        /* High 16 bit */
+        5 => "R_MIPS_HI16",
+        /* Low 16 bit */
+        6 => "R_MIPS_LO16",
+        /* GP relative 16 bit */
+        7 => "R_MIPS_GPREL16",
+        /* 16 bit literal entry */
+        8 => "R_MIPS_LITERAL",
+        /* 16 bit GOT entry */
+        9 => "R_MIPS_GOT16",
+        /* PC relative 16 bit */
+        10 => "R_MIPS_PC16",
+        /* 16 bit GOT entry for function */
+        11 => "R_MIPS_CALL16",
+        /* GP relative 32 bit */
+        12 => "R_MIPS_GPREL32",
+        16 => "R_MIPS_SHIFT5",
+        17 => "R_MIPS_SHIFT6",
+        /* Direct 64 bit */
+        18 => "R_MIPS_64",
+        19 => "R_MIPS_GOT_DISP",
+        20 => "R_MIPS_GOT_PAGE",
+        21 => "R_MIPS_GOT_OFST",
+        22 => "R_MIPS_GOT_HI16",
+        23 => "R_MIPS_GOT_LO16",
+        24 => "R_MIPS_SUB",
+        25 => "R_MIPS_INSERT_A",
+        26 => "R_MIPS_INSERT_B",
+        27 => "R_MIPS_DELETE",
+        28 => "R_MIPS_HIGHER",
+        29 => "R_MIPS_HIGHEST",
+        30 => "R_MIPS_CALL_HI16",
+        31 => "R_MIPS_CALL_LO16",
+        32 => "R_MIPS_SCN_DISP",
+        33 => "R_MIPS_REL16",
+        34 => "R_MIPS_ADD_IMMEDIATE",
+        35 => "R_MIPS_PJUMP",
+        36 => "R_MIPS_RELGOT",
+        /* Call through register, hint for the linker */
+        37 => "R_MIPS_JALR",
+        /* Module number 32 bit */
+        38 => "R_MIPS_TLS_DTPMOD32",
+        /* Module-relative offset 32 bit */
+        39 => "R_MIPS_TLS_DTPREL32",
+        /* Module number 64 bit */
+        40 => "R_MIPS_TLS_DTPMOD64",
+        /* Module-relative offset 64 bit */
+        41 => "R_MIPS_TLS_DTPREL64",
+        /* 16 bit GOT offset for GD */
+        42 => "R_MIPS_TLS_GD",
+        /* 16 bit GOT offset for LDM */
+        43 => "R_MIPS_TLS_LDM",
+        /* Module-relative offset, high 16 bits */
+        44 => "R_MIPS_TLS_DTPREL_HI16",
+        /* Module-relative offset, low 16 bits */
+        45 => "R_MIPS_TLS_DTPREL_LO16",
+        /* 16 bit GOT offset for IE */
+        46 => "R_MIPS_TLS_GOTTPREL",
+        /* TP-relative offset, 32 bit */
+        47 => "R_MIPS_TLS_TPREL32",
+        /* TP-relative offset, 64 bit */
+        48 => "R_MIPS_TLS_TPREL64",
+        /* TP-relative offset, high 16 bits */
+        49 => "R_MIPS_TLS_TPREL_HI16",
+        /* TP-relative offset, low 16 bits */
+        50 => "R_MIPS_TLS_TPREL_LO16",
+        51 => "R_MIPS_GLOB_DAT",
+        126 => "R_MIPS_COPY",
+        127 => "R_MIPS_JUMP_SLOT",
+        _ => "Unknown",
+    }
+}
+
+fn ppc_relocs(value: u32) -> &'static str {
+    match value {
+        /* No reloc */
+        0 => "R_PPC_NONE",
+        /* 32bit absolute address */
+        1 => "R_PPC_ADDR32",
+        2 => "R_PPC_ADDR24",
+        3 => "R_PPC_ADDR16",
+        4 => "R_PPC_ADDR16_LO",
+        5 => "R_PPC_ADDR16_HI",
+        6 => "R_PPC_ADDR16_HA",
+        7 => "R_PPC_ADDR14",
+        8 => "R_PPC_ADDR14_BRTAKEN",
+        9 => "R_PPC_ADDR14_BRNTAKEN",
+        /* PC relative 26 bit */
+        10 => "R_PPC_REL24",
+        11 => "R_PPC_REL14",
+        12 => "R_PPC_REL14_BRTAKEN",
+        13 => "R_PPC_REL14_BRNTAKEN",
+        14 => "R_PPC_GOT16",
+        15 => "R_PPC_GOT16_LO",
+        16 => "R_PPC_GOT16_HI",
+        17 => "R_PPC_GOT16_HA",
+        18 => "R_PPC_PLTREL24",
+        19 => "R_PPC_COPY",
+        /* Create GOT entry */
+        20 => "R_PPC_GLOB_DAT",
+        /* Create PLT entry */
+        21 => "R_PPC_JMP_SLOT",
+        /* Adjust by program base */
+        22 => "R_PPC_RELATIVE",
+        23 => "R_PPC_LOCAL24PC",
+        24 => "R_PPC_UADDR32",
+        25 => "R_PPC_UADDR16",
+        26 => "R_PPC_REL32",
+        27 => "R_PPC_PLT32",
+        28 => "R_PPC_PLTREL32",
+        29 => "R_PPC_PLT16_LO",
+        30 => "R_PPC_PLT16_HI",
+        31 => "R_PPC_PLT16_HA",
+        32 => "R_PPC_SDAREL16",
+        33 => "R_PPC_SECTOFF",
+        34 => "R_PPC_SECTOFF_LO",
+        35 => "R_PPC_SECTOFF_HI",
+        36 => "R_PPC_SECTOFF_HA",
+        /* Thread-local storage relocations */
+        67 => "R_PPC_TLS",
+        68 => "R_PPC_DTPMOD32",
+        69 => "R_PPC_TPREL16",
+        70 => "R_PPC_TPREL16_LO",
+        71 => "R_PPC_TPREL16_HI",
+        72 => "R_PPC_TPREL16_HA",
+        73 => "R_PPC_TPREL32",
+        74 => "R_PPC_DTPREL16",
+        75 => "R_PPC_DTPREL16_LO",
+        76 => "R_PPC_DTPREL16_HI",
+        77 => "R_PPC_DTPREL16_HA",
+        78 => "R_PPC_DTPREL32",
+        79 => "R_PPC_GOT_TLSGD16",
+        80 => "R_PPC_GOT_TLSGD16_LO",
+        81 => "R_PPC_GOT_TLSGD16_HI",
+        82 => "R_PPC_GOT_TLSGD16_HA",
+        83 => "R_PPC_GOT_TLSLD16",
+        84 => "R_PPC_GOT_TLSLD16_LO",
+        85 => "R_PPC_GOT_TLSLD16_HI",
+        86 => "R_PPC_GOT_TLSLD16_HA",
+        87 => "R_PPC_GOT_TPREL16",
+        88 => "R_PPC_GOT_TPREL16_LO",
+        89 => "R_PPC_GOT_TPREL16_HI",
+        90 => "R_PPC_GOT_TPREL16_HA",
+        91 => "R_PPC_GOT_DTPREL16",
+        92 => "R_PPC_GOT_DTPREL16_LO",
+        93 => "R_PPC_GOT_DTPREL16_HI",
+        94 => "R_PPC_GOT_DTPREL16_HA",
+        95 => "R_PPC_TLSGD",
+        96 => "R_PPC_TLSLD",
+        /* Adjust indirectly by program base */
+        248 => "R_PPC_IRELATIVE",
+        249 => "R_PPC_REL16",
+        250 => "R_PPC_REL16_LO",
+        251 => "R_PPC_REL16_HI",
+        252 => "R_PPC_REL16_HA",
+        _ => "Unknown",
+    }
+}
+
+fn ppc64_relocs(value: u32) -> &'static str {
+    match value {
+        /* No reloc */
+        0 => "R_PPC64_NONE",
+        1 => "R_PPC64_ADDR32",
+        2 => "R_PPC64_ADDR24",
+        3 => "R_PPC64_ADDR16",
+        4 => "R_PPC64_ADDR16_LO",
+        5 => "R_PPC64_ADDR16_HI",
+        6 => "R_PPC64_ADDR16_HA",
+        7 => "R_PPC64_ADDR14",
+        8 => "R_PPC64_ADDR14_BRTAKEN",
+        9 => "R_PPC64_ADDR14_BRNTAKEN",
+        /* PC relative 26 bit */
+        10 => "R_PPC64_REL24",
+        11 => "R_PPC64_REL14",
+        12 => "R_PPC64_REL14_BRTAKEN",
+        13 => "R_PPC64_REL14_BRNTAKEN",
+        14 => "R_PPC64_GOT16",
+        15 => "R_PPC64_GOT16_LO",
+        16 => "R_PPC64_GOT16_HI",
+        17 => "R_PPC64_GOT16_HA",
+        19 => "R_PPC64_COPY",
+        /* Create GOT entry */
+        20 => "R_PPC64_GLOB_DAT",
+        /* Create PLT entry */
+        21 => "R_PPC64_JMP_SLOT",
+        /* Adjust by program base */
+        22 => "R_PPC64_RELATIVE",
+        24 => "R_PPC64_UADDR32",
+        25 => "R_PPC64_UADDR16",
+        26 => "R_PPC64_REL32",
+        27 => "R_PPC64_PLT32",
+        28 => "R_PPC64_PLTREL32",
+        29 => "R_PPC64_PLT16_LO",
+        30 => "R_PPC64_PLT16_HI",
+        31 => "R_PPC64_PLT16_HA",
+        33 => "R_PPC64_SECTOFF",
+        34 => "R_PPC64_SECTOFF_LO",
+        35 => "R_PPC64_SECTOFF_HI",
+        36 => "R_PPC64_SECTOFF_HA",
+        37 => "R_PPC64_ADDR30",
+        /* Direct 64 bit */
+        38 => "R_PPC64_ADDR64",
+        39 => "R_PPC64_ADDR16_HIGHER",
+        40 => "R_PPC64_ADDR16_HIGHERA",
+        41 => "R_PPC64_ADDR16_HIGHEST",
+        42 => "R_PPC64_ADDR16_HIGHESTA",
+        43 => "R_PPC64_UADDR64",
+        44 => "R_PPC64_REL64",
+        45 => "R_PPC64_PLT64",
+        46 => "R_PPC64_PLTREL64",
+        /* 16 bit TOC-relative offset */
+        47 => "R_PPC64_TOC16",
+        48 => "R_PPC64_TOC16_LO",
+        49 => "R_PPC64_TOC16_HI",
+        50 => "R_PPC64_TOC16_HA",
+        /* Address of the TOC base */
+        51 => "R_PPC64_TOC",
+        52 => "R_PPC64_PLTGOT16",
+        53 => "R_PPC64_PLTGOT16_LO",
+        54 => "R_PPC64_PLTGOT16_HI",
+        55 => "R_PPC64_PLTGOT16_HA",
+        56 => "R_PPC64_ADDR16_DS",
+        57 => "R_PPC64_ADDR16_LO_DS",
+        58 => "R_PPC64_GOT16_DS",
+        59 => "R_PPC64_GOT16_LO_DS",
+        60 => "R_PPC64_PLT16_LO_DS",
+        61 => "R_PPC64_SECTOFF_DS",
+        62 => "R_PPC64_SECTOFF_LO_DS",
+        63 => "R_PPC64_TOC16_DS",
+        64 => "R_PPC64_TOC16_LO_DS",
+        65 => "R_PPC64_PLTGOT16_DS",
+        66 => "R_PPC64_PLTGOT16_LO_DS",
+        /* Thread-local storage relocations */
+        67 => "R_PPC64_TLS",
+        68 => "R_PPC64_DTPMOD64",
+        69 => "R_PPC64_TPREL16",
+        70 => "R_PPC64_TPREL16_LO",
+        71 => "R_PPC64_TPREL16_HI",
+        72 => "R_PPC64_TPREL16_HA",
+        73 => "R_PPC64_TPREL64",
+        74 => "R_PPC64_DTPREL16",
+        75 => "R_PPC64_DTPREL16_LO",
+        76 => "R_PPC64_DTPREL16_HI",
+        77 => "R_PPC64_DTPREL16_HA",
+        78 => "R_PPC64_DTPREL64",
+        79 => "R_PPC64_GOT_TLSGD16",
+        80 => "R_PPC64_GOT_TLSGD16_LO",
+        81 => "R_PPC64_GOT_TLSGD16_HI",
+        82 => "R_PPC64_GOT_TLSGD16_HA",
+        83 => "R_PPC64_GOT_TLSLD16",
+        84 => "R_PPC64_GOT_TLSLD16_LO",
+        85 => "R_PPC64_GOT_TLSLD16_HI",
+        86 => "R_PPC64_GOT_TLSLD16_HA",
+        87 => "R_PPC64_GOT_TPREL16_DS",
+        88 => "R_PPC64_GOT_TPREL16_LO_DS",
+        89 => "R_PPC64_GOT_TPREL16_HI",
+        90 => "R_PPC64_GOT_TPREL16_HA",
+        91 => "R_PPC64_GOT_DTPREL16_DS",
+        92 => "R_PPC64_GOT_DTPREL16_LO_DS",
+        93 => "R_PPC64_GOT_DTPREL16_HI",
+        94 => "R_PPC64_GOT_DTPREL16_HA",
+        95 => "R_PPC64_TPREL16_DS",
+        96 => "R_PPC64_TPREL16_LO_DS",
+        97 => "R_PPC64_TPREL16_HIGHER",
+        98 => "R_PPC64_TPREL16_HIGHERA",
+        99 => "R_PPC64_TPREL16_HIGHEST",
+        100 => "R_PPC64_TPREL16_HIGHESTA",
+        101 => "R_PPC64_DTPREL16_DS",
+        102 => "R_PPC64_DTPREL16_LO_DS",
+        103 => "R_PPC64_DTPREL16_HIGHER",
+        104 => "R_PPC64_DTPREL16_HIGHERA",
+        105 => "R_PPC64_DTPREL16_HIGHEST",
+        106 => "R_PPC64_DTPREL16_HIGHESTA",
+        107 => "R_PPC64_TLSGD",
+        108 => "R_PPC64_TLSLD",
+        109 => "R_PPC64_TOCSAVE",
+        110 => "R_PPC64_ADDR16_HIGH",
+        111 => "R_PPC64_ADDR16_HIGHA",
+        112 => "R_PPC64_TPREL16_HIGH",
+        113 => "R_PPC64_TPREL16_HIGHA",
+        114 => "R_PPC64_DTPREL16_HIGH",
+        115 => "R_PPC64_DTPREL16_HIGHA",
+        116 => "R_PPC64_REL24_NOTOC",
+        117 => "R_PPC64_ADDR64_LOCAL",
+        118 => "R_PPC64_ENTRY",
+        247 => "R_PPC64_JMP_IREL",
+        /* Adjust indirectly by program base */
+        248 => "R_PPC64_IRELATIVE",
+        249 => "R_PPC64_REL16",
+        250 => "R_PPC64_REL16_LO",
+        251 => "R_PPC64_REL16_HI",
+        252 => "R_PPC64_REL16_HA",
+        _ => "Unknown",
+    }
+}
+
+// Relocations of eBPF objects, these are resolved by the loader (libbpf)
+// against maps, subprograms and kernel symbols, not by a dynamic linker
+fn bpf_relocs(value: u32) -> &'static str {
+    match value {
+        0 => "R_BPF_NONE",
+        /* 64bit immediate of ld_imm64 instruction */
+        1 => "R_BPF_64_64",
+        2 => "R_BPF_64_ABS64",
+        3 => "R_BPF_64_ABS32",
+        4 => "R_BPF_64_NODYLD32",
+        /* call instruction offset */
+        10 => "R_BPF_64_32",
+        _ => "Unknown",
+    }
+}
+
+/* Value written by the relocation and the formula used to compute it,
+ * only for types with a simple formula:
+ *    S - symbol value
+ *    A - addend
+ *    P - place (address of the relocated field)
+ *    B - load base, assumed to be zero
+ */
+fn relocated_value(
+    machine: u16,
+    reltype: u32,
+    symbol: u64,
+    addend: i64,
+    place: u64,
+) -> Option<(u64, &'static str)> {
+    let addend = addend as u64;
+
+    match (machine, reltype) {
+        // R_X86_64_64
+        (62, 1) => Some((symbol.wrapping_add(addend), "S + A")),
+        // R_X86_64_PC32, the field is 32 bits wide
+        (62, 2) => Some((
+            symbol.wrapping_add(addend).wrapping_sub(place) & 0xffffffff,
+            "S + A - P",
+        )),
+        // R_X86_64_GLOB_DAT, R_X86_64_JUMP_SLOT
+        (62, 6) | (62, 7) => Some((symbol, "S")),
+        // R_X86_64_RELATIVE
+        (62, 8) => Some((addend, "B + A")),
+        _ => None,
+    }
+}
+
+// Name of the relocation type, the numbering is specific to each machine,
+// types without a name are shown as a number
+pub fn reloc_name(machine: u16, value: u32) -> Cow<'static, str> {
+    let name = match machine {
+        8 | 10 => mips_relocs(value),
+        20 => ppc_relocs(value),
+        21 => ppc64_relocs(value),
+        62 => amd64_relocs(value),
+        247 => bpf_relocs(value),
+        _ => "Unknown",
+    };
+
+    match name {
+        "Unknown" => Cow::Owned(value.to_string()),
+        _ => Cow::Borrowed(name),
+    }
+}
+
 #[derive(Debug)]
 pub struct RelocationEntry {
     // Address
@@ -101,6 +485,9 @@ pub struct RelocationEntry {
     symidx: u32,
     // Addend (present only for Rela section)
     addend: Option<i64>,
+    // Second and third relocation type, MIPS64 packs up to three
+    // relocations into a single entry
+    extra_types: Vec<u32>,
 }
 
 #[derive(Debug)]
@@ -108,31 +495,143 @@ pub struct RelocationSection {
     pub entries: Vec<RelocationEntry>,
     pub symtab: SymbolTable,
     pub name: String,
+    // Section the relocations apply to, from `sh_info'
+    pub target: Option<String>,
     pub kind: SectionHeaderType,
+    pub machine: u16,
+    // File ended before the end of the section
+    pub truncated: bool,
+    // Show the value written by relocations with a known formula
+    pub values: bool,
+    // Longer symbol names are cut in the listing
+    pub width: Option<usize>,
+}
+
+// Compact encoding of relative relocations, the section consists
+// of address entries (even words) followed by bitmap entries (odd words)
+// where each set bit marks relocated word after the last address
+#[derive(Debug)]
+pub struct RelrSection {
+    pub offsets: Vec<u64>,
+    pub name: String,
+    pub machine: u16,
+}
+
+// PLT stubs paired with GOT slots and symbols they resolve to
+#[derive(Debug)]
+pub struct PltEntries {
+    // Stub address (if known), GOT slot address, index of the symbol in
+    // the dynamic symbol table and its name
+    entries: Vec<(Option<u64>, u64, usize, String)>,
+    // Stubs are in `.plt.sec' (CET enabled binaries)
+    split: bool,
+}
+
+// Lazy binding chain of an imported function
+#[derive(Debug)]
+struct PltTraceEntry {
+    name: String,
+    stub: Option<u64>,
+    // GOT slot the stub jumps through
+    got: u64,
+    // Value of the slot before binding, it points back to the lazy
+    // binding code in `.plt'
+    initial: Option<u64>,
+    // Version and library the symbol is expected from
+    version: Option<(String, String)>,
+}
+
+// PLT stubs followed to GOT slots and symbols they are bound to
+#[derive(Debug)]
+pub struct PltTrace {
+    entries: Vec<PltTraceEntry>,
+    split: bool,
+}
+
+// Single GOT slot
+#[derive(Debug)]
+struct GotEntry {
+    address: u64,
+    // Value stored in the file, before any relocation is applied
+    value: u64,
+    // Type of the dynamic relocation of the slot and its symbol
+    reloc: Option<(Cow<'static, str>, String)>,
+    // Slot is read-only after relocation (covered by PT_GNU_RELRO)
+    relro: bool,
+}
+
+// Slots of `.got' and `.got.plt' sections
+#[derive(Debug)]
+pub struct GotSections {
+    sections: Vec<(String, Vec<GotEntry>)>,
 }
 
 #[derive(Debug)]
 pub struct RelocationSections {
     pub sections: Vec<RelocationSection>,
+    pub relr: Vec<RelrSection>,
+}
+
+// Symbol table of the relocation section, sections without symbols (e.g.
+// IRELATIVE relocations of static executables have zero `sh_link') and
+// sections linking outside of the section headers get an empty table
+fn linked_symtab(
+    headers: &SectionHeaders,
+    header: &SectionHeader,
+    machine: u16,
+    reader: &mut Reader,
+) -> Result<SymbolTable> {
+    match headers.try_get_by_index(header.sh_link as usize) {
+        Ok(symtab_header) if header.sh_link != 0 => {
+            SymbolTable::new(headers, &symtab_header, machine, reader)
+        }
+        _ => Ok(SymbolTable::empty(machine)),
+    }
 }
 
 impl RelocationEntry {
-    fn new(reader: &mut Reader, has_addend: bool) -> RelocationEntry {
-        let offset = reader.read_u64::<LittleEndian>().unwrap();
-        let reltype = reader.read_u32::<LittleEndian>().unwrap();
-        let symidx = reader.read_u32::<LittleEndian>().unwrap();
+    fn new(reader: &mut Reader, has_addend: bool, machine: u16) -> Result<RelocationEntry> {
+        let offset = reader.read_u64::<LittleEndian>()?;
+        let mut extra_types = vec![];
+
+        let (reltype, symidx) = if machine == 8 || machine == 10 {
+            /* MIPS64 r_info layout:
+             *   Elf64_Word r_sym;
+             *   unsigned char r_ssym;
+             *   unsigned char r_type3;
+             *   unsigned char r_type2;
+             *   unsigned char r_type;
+             */
+            let symidx = reader.read_u32::<LittleEndian>()?;
+            let _ssym = reader.read_u8()?;
+            let type3 = reader.read_u8()? as u32;
+            let type2 = reader.read_u8()? as u32;
+            let reltype = reader.read_u8()? as u32;
+
+            // R_MIPS_NONE marks an unused slot
+            extra_types.extend([type2, type3].iter().filter(|reltype| **reltype != 0));
+
+            (reltype, symidx)
+        } else {
+            let reltype = reader.read_u32::<LittleEndian>()?;
+            let symidx = reader.read_u32::<LittleEndian>()?;
+
+            (reltype, symidx)
+        };
+
         let addend = if has_addend {
-            Some(reader.read_i64::<LittleEndian>().unwrap())
+            Some(reader.read_i64::<LittleEndian>()?)
         } else {
             None
         };
 
-        RelocationEntry {
+        Ok(RelocationEntry {
             offset,
             reltype,
             symidx,
             addend,
-        }
+            extra_types,
+        })
     }
 }
 
@@ -140,49 +639,477 @@ impl RelocationSection {
     pub fn new(
         header: &SectionHeader,
         name: String,
+        target: Option<String>,
         symtab: SymbolTable,
+        machine: u16,
         reader: &mut Reader,
-    ) -> RelocationSection {
-        let mut entries = vec![];
-        let mut offset = 0;
+    ) -> Result<RelocationSection> {
+        let has_addend = header.sh_type == SectionHeaderType::Rela;
+        let entsize = header.entsize();
 
-        while offset < header.sh_size {
-            reader
-                .seek(SeekFrom::Start(header.sh_offset + offset))
-                .unwrap();
+        // smaller entries would overlap
+        if entsize < if has_addend { RELA_SIZE } else { REL_SIZE } {
+            return Err(ElfError::Malformed(format!(
+                "relocation entry size {}",
+                entsize
+            )));
+        }
+
+        let mut entries = vec![];
 
-            let has_addend = header.sh_type == SectionHeaderType::Rela;
+        // read only entries present in the file
+        let size = reader.available(header.sh_offset, header.sh_size);
 
-            entries.push(RelocationEntry::new(reader, has_addend));
-            offset += header.sh_entsize;
+        for i in 0..size / entsize {
+            reader.seek(SeekFrom::Start(header.sh_offset + i * entsize))?;
+            entries.push(RelocationEntry::new(reader, has_addend, machine)?);
         }
 
-        RelocationSection {
+        Ok(RelocationSection {
             symtab,
             name,
+            target,
             entries,
             kind: header.sh_type.clone(),
+            machine,
+            truncated: size < header.sh_size,
+            values: false,
+            width: None,
+        })
+    }
+}
+
+impl RelrSection {
+    pub fn new(
+        header: &SectionHeader,
+        name: String,
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<RelrSection> {
+        reader.check_range(header.sh_offset, header.sh_size)?;
+        reader.seek(SeekFrom::Start(header.sh_offset))?;
+
+        let mut offsets = vec![];
+        let mut base: u64 = 0;
+        let mut offset = 0;
+
+        while offset < header.sh_size {
+            let entry = reader.read_u64::<LittleEndian>()?;
+
+            // corrupted entries may wrap around the address space
+            if entry & 1 == 0 {
+                // Address entry, it also starts the next bitmap
+                offsets.push(entry);
+                base = entry.wrapping_add(8);
+            } else {
+                // Bitmap entry, bit 0 is the marker, bits 1..63 mark words
+                for bit in 1..64 {
+                    if (entry >> bit) & 1 == 1 {
+                        offsets.push(base.wrapping_add((bit - 1) * 8));
+                    }
+                }
+                base = base.wrapping_add(63 * 8);
+            }
+
+            offset += 8;
         }
+
+        Ok(RelrSection {
+            offsets,
+            name,
+            machine,
+        })
     }
 }
 
 impl RelocationSections {
-    pub fn new(headers: &SectionHeaders, mut reader: &mut Reader) -> RelocationSections {
+    // RELR sections hold only offsets of relative relocations
+    pub fn to_json(&self) -> String {
+        let sections: Vec<String> = self
+            .sections
+            .iter()
+            .map(|section| {
+                let entries: Vec<String> = section
+                    .entries
+                    .iter()
+                    .map(|entry| {
+                        let (name, _) = section.symtab.get_by_index(entry.symidx as usize);
+
+                        json::object(&[
+                            ("offset", json::hex(entry.offset)),
+                            (
+                                "type",
+                                json::string(&reloc_name(section.machine, entry.reltype)),
+                            ),
+                            ("symbol", json::string(&name)),
+                            (
+                                "addend",
+                                json::or_null(entry.addend.map(|addend| addend.to_string())),
+                            ),
+                        ])
+                    })
+                    .collect();
+
+                json::object(&[
+                    ("name", json::string(&section.name)),
+                    (
+                        "target",
+                        json::or_null(section.target.as_deref().map(json::string)),
+                    ),
+                    ("entries", json::array(&entries)),
+                ])
+            })
+            .collect();
+
+        let relr: Vec<String> = self
+            .relr
+            .iter()
+            .map(|section| {
+                let offsets: Vec<String> = section
+                    .offsets
+                    .iter()
+                    .map(|offset| json::hex(*offset))
+                    .collect();
+
+                json::object(&[
+                    ("name", json::string(&section.name)),
+                    ("offsets", json::array(&offsets)),
+                ])
+            })
+            .collect();
+
+        json::object(&[
+            ("sections", json::array(&sections)),
+            ("relr", json::array(&relr)),
+        ])
+    }
+
+    pub fn new(
+        headers: &SectionHeaders,
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<RelocationSections> {
         let mut sections: Vec<RelocationSection> = vec![];
 
         let mut rel_headers = headers.get_all(SectionHeaderType::Rel);
         rel_headers.extend(headers.get_all(SectionHeaderType::Rela));
 
         for header in &rel_headers {
-            let symtab_header = headers.get_by_index(header.sh_link as usize);
+            let name = headers.strtab.get(header.sh_name as u64);
+            let target = headers.info_link_name(header);
+            let symtab = linked_symtab(headers, header, machine, reader)?;
 
+            sections.push(RelocationSection::new(
+                header, name, target, symtab, machine, reader,
+            )?);
+        }
+
+        let mut relr: Vec<RelrSection> = vec![];
+
+        for header in &headers.get_all(SectionHeaderType::Relr) {
             let name = headers.strtab.get(header.sh_name as u64);
-            let symtab = SymbolTable::new(&headers, &symtab_header, &mut reader);
+            relr.push(RelrSection::new(header, name, machine, reader)?);
+        }
+
+        Ok(RelocationSections { sections, relr })
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.sections.iter().any(|section| section.truncated)
+    }
+
+    // Values are computed only for the types known to `relocated_value',
+    // the others show just the raw fields
+    pub fn set_values(&mut self, values: bool) {
+        for section in &mut self.sections {
+            section.values = values;
+        }
+    }
+
+    pub fn set_width(&mut self, width: Option<usize>) {
+        for section in &mut self.sections {
+            section.width = width;
+        }
+    }
+
+    // Type and symbol name of the relocation applied at `address'
+    fn find(&self, address: u64) -> Option<(Cow<'static, str>, String)> {
+        for section in &self.sections {
+            for entry in &section.entries {
+                if entry.offset != address {
+                    continue;
+                }
+
+                let name = match entry.symidx {
+                    0 => String::new(),
+                    index => section.symtab.get_by_index(index as usize).0,
+                };
+
+                return Some((reloc_name(section.machine, entry.reltype), name));
+            }
+        }
+
+        None
+    }
+
+    // Location and resolver address of every IRELATIVE relocation, the
+    // addend holds the address of the ifunc resolver
+    pub fn irelative(&self) -> Vec<(u64, u64)> {
+        let mut result = vec![];
+
+        for section in &self.sections {
+            for entry in &section.entries {
+                if !reloc_name(section.machine, entry.reltype).ends_with("_IRELATIVE") {
+                    continue;
+                }
+                if let Some(addend) = entry.addend {
+                    result.push((entry.offset, addend as u64));
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl PltEntries {
+    pub fn new(headers: &SectionHeaders, machine: u16, reader: &mut Reader) -> Result<PltEntries> {
+        let mut entries = vec![];
+
+        let header = match headers
+            .get_by_name(".rela.plt")
+            .or_else(|| headers.get_by_name(".rel.plt"))
+        {
+            Some(header) => header,
+            None => {
+                return Ok(PltEntries {
+                    entries,
+                    split: false,
+                })
+            }
+        };
+
+        let name = headers.strtab.get(header.sh_name as u64);
+        let symtab = linked_symtab(headers, &header, machine, reader)?;
+        let target = headers.info_link_name(&header);
+        let section = RelocationSection::new(&header, name, target, symtab, machine, reader)?;
+
+        // With IBT enabled the lazy binding stubs stay in `.plt' and calls go
+        // through `.plt.sec', otherwise the first `.plt' entry is reserved
+        let (plt, first, split) = match headers.get_by_name(".plt.sec") {
+            Some(plt) => (Some(plt), 0, true),
+            None => (headers.get_by_name(".plt"), 1, false),
+        };
+
+        for (n, entry) in section.entries.iter().enumerate() {
+            let name = if (entry.symidx as usize) < section.symtab.len() {
+                section.symtab.get_by_index(entry.symidx as usize).0
+            } else {
+                String::new()
+            };
+
+            // the stubs are sh_entsize apart, only on x86 the reserved
+            // first `.plt' entry has the same size as the others
+            let stub = match (&plt, machine) {
+                (Some(plt), 3) | (Some(plt), 62) if plt.sh_entsize != 0 => {
+                    Some(plt.sh_addr + (n as u64 + first) * plt.sh_entsize)
+                }
+                _ => None,
+            };
+
+            entries.push((stub, entry.offset, entry.symidx as usize, name));
+        }
+
+        Ok(PltEntries { entries, split })
+    }
+}
+
+impl PltTrace {
+    pub fn new(
+        headers: &SectionHeaders,
+        prheaders: &ProgramHeaders,
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<PltTrace> {
+        let plt = PltEntries::new(headers, machine, reader)?;
+        // the stubs need versions only up to the highest symbol index
+        let count = plt.entries.iter().map(|entry| entry.2 as u64 + 1).max();
+        let versions = symbol_versions(headers, prheaders, count.unwrap_or(0), reader)?;
+        let verneed = VersionSection::new(headers, prheaders, reader)?;
 
-            sections.push(RelocationSection::new(&header, name, symtab, reader));
+        let mut entries = vec![];
+
+        for (stub, got, symidx, name) in plt.entries {
+            let initial = match prheaders.vaddr_to_offset(got) {
+                Some(offset) if reader.check_range(offset, 8).is_ok() => {
+                    reader.seek(SeekFrom::Start(offset))?;
+                    Some(reader.read_u64::<LittleEndian>()?)
+                }
+                _ => None,
+            };
+
+            // the top bit marks hidden symbol
+            let version = match (versions.get(symidx), &verneed) {
+                (Some(version), Some(verneed)) => verneed.requirement(version & 0x7fff),
+                _ => None,
+            };
+
+            entries.push(PltTraceEntry {
+                name,
+                stub,
+                got,
+                initial,
+                version,
+            });
+        }
+
+        Ok(PltTrace {
+            entries,
+            split: plt.split,
+        })
+    }
+}
+
+impl GotSections {
+    pub fn new(
+        headers: &SectionHeaders,
+        prheaders: &ProgramHeaders,
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<GotSections> {
+        let relocs = RelocationSections::new(headers, machine, reader)?;
+        let relro = prheaders.get_all(SegmentType::GnuRelRo);
+        let mut sections = vec![];
+
+        for name in &[".got", ".got.plt"] {
+            let header = match headers.get_by_name(name) {
+                Some(header) => header,
+                None => continue,
+            };
+
+            reader.check_range(header.sh_offset, header.sh_size)?;
+            reader.seek(SeekFrom::Start(header.sh_offset))?;
+
+            let mut entries = vec![];
+
+            for n in 0..header.sh_size / 8 {
+                let address = header.sh_addr + n * 8;
+
+                entries.push(GotEntry {
+                    address,
+                    value: reader.read_u64::<LittleEndian>()?,
+                    reloc: relocs.find(address),
+                    relro: relro.iter().any(|segment| {
+                        address >= segment.p_vaddr && address - segment.p_vaddr < segment.p_memsiz
+                    }),
+                });
+            }
+
+            sections.push((name.to_string(), entries));
+        }
+
+        Ok(GotSections { sections })
+    }
+}
+
+impl fmt::Display for PltEntries {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let plt = if self.split { ".plt.sec" } else { ".plt" };
+
+        writeln!(f, "PLT `{}' contains {} entries:", plt, self.entries.len())?;
+        writeln!(f, "{:<18} {:<18} Symbol", "Stub", "GOT")?;
+
+        for (stub, got, _, name) in &self.entries {
+            let stub = match stub {
+                Some(stub) => format!("{:#018x}", stub),
+                None => String::from("-"),
+            };
+
+            let name = if name.is_empty() {
+                String::from("*ABS*")
+            } else {
+                format!("{}@plt -> {}", name, name)
+            };
+
+            writeln!(f, "{:<18} {:#018x} {}", stub, got, name)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PltTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.entries.is_empty() {
+            return writeln!(f, "There are no PLT entries in this file");
         }
 
-        RelocationSections { sections }
+        let plt = if self.split { ".plt.sec" } else { ".plt" };
+
+        for entry in &self.entries {
+            let name = if entry.name.is_empty() {
+                "*ABS*"
+            } else {
+                &entry.name
+            };
+
+            writeln!(f, "{}:", name)?;
+
+            match entry.stub {
+                Some(stub) => writeln!(f, "    {:<10} {:#x} ({})", "PLT stub:", stub, plt)?,
+                None => writeln!(f, "    {:<10} -", "PLT stub:")?,
+            }
+
+            write!(f, "    {:<10} {:#x}", "GOT slot:", entry.got)?;
+
+            match entry.initial {
+                Some(initial) => writeln!(f, ", initially {:#x}", initial)?,
+                None => writeln!(f)?,
+            }
+
+            match &entry.version {
+                Some((version, file)) => {
+                    writeln!(f, "    {:<10} {}@{} ({})", "Symbol:", name, version, file)?
+                }
+                None => writeln!(f, "    {:<10} {}", "Symbol:", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GotSections {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.sections.is_empty() {
+            return writeln!(f, "There is no GOT in this file");
+        }
+
+        for (name, entries) in &self.sections {
+            writeln!(f, "GOT `{}' contains {} entries:", name, entries.len())?;
+            writeln!(
+                f,
+                "{:<18} {:<18} {:<6} {:<20} Symbol",
+                "Address", "Value", "RELRO", "Type"
+            )?;
+
+            for entry in entries {
+                let (reltype, name) = match &entry.reloc {
+                    Some((reltype, name)) => (reltype.as_ref(), name.as_str()),
+                    None => ("-", ""),
+                };
+
+                writeln!(
+                    f,
+                    "{:#018x} {:#018x} {:<6} {:<20} {}",
+                    entry.address,
+                    entry.value,
+                    if entry.relro { "yes" } else { "no" },
+                    reltype,
+                    name
+                )?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
     }
 }
 
@@ -194,29 +1121,50 @@ impl fmt::Display for RelocationSections {
             result = section.fmt(f);
             writeln!(f)?;
         }
+
+        for section in &self.relr {
+            result = section.fmt(f);
+            writeln!(f)?;
+        }
         result
     }
 }
 
 impl fmt::Display for RelocationSection {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        writeln!(
+        write!(
             f,
-            "Relocation section `{}' contains {} entries:",
+            "Relocation section `{}' contains {} entries",
             self.name,
             self.entries.len()
         )?;
+        match &self.target {
+            Some(target) => writeln!(f, " (applies to: {}):", target)?,
+            None => writeln!(f, ":")?,
+        }
 
         writeln!(
             f,
             "{:<6} {:<12} {:<20} {:<12} {:<16}",
             "Num", "Sym. Size", "Sym. Type", "Sym. Bind", "Sym. Vis",
         )?;
-        writeln!(
-            f,
-            "       {:<12} {:<20} {:<12} {:<16} {:<16}",
-            "Offset", "Type", "Sym. Value", "Addend", "Sym. Name"
-        )?;
+
+        // REL entries store the addend in the relocated field
+        let values = self.values && self.kind == SectionHeaderType::Rela;
+
+        if values {
+            writeln!(
+                f,
+                "       {:<12} {:<20} {:<12} {:<16} {:<26} {:<16}",
+                "Offset", "Type", "Sym. Value", "Addend", "Value (load base 0)", "Sym. Name"
+            )?;
+        } else {
+            writeln!(
+                f,
+                "       {:<12} {:<20} {:<12} {:<16} {:<16}",
+                "Offset", "Type", "Sym. Value", "Addend", "Sym. Name"
+            )?;
+        }
 
         for (n, entry) in self.entries.iter().enumerate() {
             let (name, symbol) = self.symtab.get_by_index(entry.symidx as usize);
@@ -231,22 +1179,262 @@ impl fmt::Display for RelocationSection {
                 n, symbol.st_size, typ, bin, vis
             )?;
 
-            let addend = if entry.addend.is_some() {
-                entry.addend.unwrap()
+            let addend = entry.addend.unwrap_or(0);
+
+            // types without a simple formula have no value
+            let value = if values {
+                let value = relocated_value(
+                    self.machine,
+                    entry.reltype,
+                    symbol.st_value,
+                    addend,
+                    entry.offset,
+                );
+
+                let value = match value {
+                    Some((value, formula)) => format!("{} = {:#x}", formula, value),
+                    None => String::from("-"),
+                };
+                format!("{:<26} ", value)
             } else {
-                0
+                String::new()
             };
 
             writeln!(
                 f,
-                "       {:#012x} {:<20} {:#012x} {:#016x} {} ",
+                "       {:#012x} {:<20} {:#012x} {:#016x} {}{} ",
                 entry.offset,
-                amd64_relocs(entry.reltype),
+                reloc_name(self.machine, entry.reltype),
                 symbol.st_value,
                 addend,
-                name
+                value,
+                truncated_name(&name, self.width)
             )?;
+
+            for reltype in &entry.extra_types {
+                writeln!(
+                    f,
+                    "       {:<12} {:<20}",
+                    "",
+                    reloc_name(self.machine, *reltype)
+                )?;
+            }
+        }
+
+        if self.truncated {
+            writeln!(f, "file truncated after {} entries", self.entries.len())?;
         }
         Ok(())
     }
 }
+
+impl fmt::Display for RelrSection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Relocation section `{}' contains {} entries:",
+            self.name,
+            self.offsets.len()
+        )?;
+        writeln!(f, "{:<6} {:<18} {:<20}", "Num", "Offset", "Type")?;
+
+        // All entries are relative relocations
+        let reltype = match self.machine {
+            8 | 10 => "R_MIPS_REL32",
+            20 => "R_PPC_RELATIVE",
+            21 => "R_PPC64_RELATIVE",
+            62 => "R_X86_64_RELATIVE",
+            _ => "Relative",
+        };
+
+        for (n, offset) in self.offsets.iter().enumerate() {
+            writeln!(f, "{:<06} {:#018x} {:<20}", n, offset, reltype)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, ElfBuilder, Section};
+
+    // SHT_PROGBITS, SHT_RELA
+    const PROGBITS: u32 = 1;
+    const RELA: u32 = 4;
+
+    fn rela(entsize: u64) -> Vec<u8> {
+        let mut data = vec![];
+
+        // R_X86_64_RELATIVE at 0x2000 with addend 0x1000
+        data.extend(&0x2000u64.to_le_bytes());
+        data.extend(&8u64.to_le_bytes());
+        data.extend(&0x1000i64.to_le_bytes());
+
+        let mut section = Section::new(".rela.dyn", RELA, data);
+        section.sh_entsize = entsize;
+
+        // DYN, x86-64
+        ElfBuilder::new(3, 62).section(section).build()
+    }
+
+    #[test]
+    fn reads_entries() {
+        let (header, mut reader) = parse(rela(24));
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let relocs = RelocationSections::new(&sections, 62, &mut reader).unwrap();
+
+        let entry = &relocs.sections[0].entries[0];
+        assert_eq!(entry.offset, 0x2000);
+        assert_eq!(reloc_name(62, entry.reltype), "R_X86_64_RELATIVE");
+        assert_eq!(entry.addend, Some(0x1000));
+    }
+
+    #[test]
+    fn rejects_small_entsize() {
+        let (header, mut reader) = parse(rela(8));
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+
+        assert!(RelocationSections::new(&sections, 62, &mut reader).is_err());
+    }
+
+    #[test]
+    fn values_of_unknown_machine() {
+        let (header, mut reader) = parse(rela(24));
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+
+        // AArch64
+        let mut relocs = RelocationSections::new(&sections, 183, &mut reader).unwrap();
+        relocs.set_values(true);
+
+        let out = relocs.to_string();
+        assert!(out.contains(" 0x00000000001000 -  "), "{}", out);
+
+        let mut relocs = RelocationSections::new(&sections, 62, &mut reader).unwrap();
+        relocs.set_values(true);
+
+        let out = relocs.to_string();
+        assert!(out.contains(" B + A = 0x1000 "), "{}", out);
+    }
+
+    #[test]
+    fn mips_packed_types() {
+        let mut data = vec![];
+
+        data.extend(&0x2000u64.to_le_bytes());
+        // r_sym, r_ssym, r_type3, r_type2 and r_type
+        data.extend(&1u32.to_le_bytes());
+        data.extend(&[0, 0, 18, 3]);
+        data.extend(&0i64.to_le_bytes());
+
+        let mut section = Section::new(".rela.dyn", RELA, data);
+        section.sh_entsize = 24;
+
+        // DYN, MIPS
+        let (header, mut reader) = parse(ElfBuilder::new(3, 8).section(section).build());
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let relocs = RelocationSections::new(&sections, 8, &mut reader).unwrap();
+
+        let entry = &relocs.sections[0].entries[0];
+        assert_eq!(reloc_name(8, entry.reltype), "R_MIPS_REL32");
+        assert_eq!(entry.extra_types, vec![18]);
+    }
+
+    #[test]
+    fn unknown_type_is_numeric() {
+        assert_eq!(reloc_name(62, 8), "R_X86_64_RELATIVE");
+        assert_eq!(reloc_name(62, 1000), "1000");
+        // AArch64 has no names yet
+        assert_eq!(reloc_name(183, 1027), "1027");
+    }
+
+    #[test]
+    fn bpf_types() {
+        // SHT_REL
+        const REL: u32 = 9;
+
+        // R_BPF_64_64 of a ld_imm64 loading a map and R_BPF_64_32 of a call
+        let mut data = vec![];
+        for (offset, reltype) in &[(0x10u64, 1u64), (0x28, 10)] {
+            data.extend(&offset.to_le_bytes());
+            data.extend(&reltype.to_le_bytes());
+        }
+
+        let mut section = Section::new(".rel.text", REL, data);
+        section.sh_entsize = 16;
+
+        // REL, eBPF
+        let data = ElfBuilder::new(1, 247).section(section).build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let relocs = RelocationSections::new(&sections, 247, &mut reader).unwrap();
+
+        let names: Vec<_> = relocs.sections[0]
+            .entries
+            .iter()
+            .map(|entry| reloc_name(247, entry.reltype))
+            .collect();
+
+        assert_eq!(names, vec!["R_BPF_64_64", "R_BPF_64_32"]);
+        assert_eq!(reloc_name(247, 5), "5");
+    }
+
+    #[test]
+    fn decodes_relr() {
+        // SHT_RELR
+        const RELR: u32 = 19;
+
+        let mut data = vec![];
+        data.extend(&0x10000u64.to_le_bytes());
+        // the first and third word after the address
+        data.extend(&0b1011u64.to_le_bytes());
+
+        let (header, mut reader) = parse(
+            ElfBuilder::new(3, 62)
+                .section(Section::new(".relr.dyn", RELR, data))
+                .build(),
+        );
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let relocs = RelocationSections::new(&sections, 62, &mut reader).unwrap();
+
+        assert_eq!(relocs.relr[0].offsets, vec![0x10000, 0x10008, 0x10018]);
+    }
+
+    fn plt_stubs(name: &'static str, entsize: u64) -> Vec<Option<u64>> {
+        let mut data = vec![];
+
+        // R_X86_64_JUMP_SLOT for two GOT slots
+        for offset in &[0x3018u64, 0x3020] {
+            data.extend(&offset.to_le_bytes());
+            data.extend(&7u64.to_le_bytes());
+            data.extend(&0i64.to_le_bytes());
+        }
+
+        let mut rela = Section::new(".rela.plt", RELA, data);
+        rela.sh_entsize = 24;
+
+        let mut plt = Section::new(name, PROGBITS, vec![0; 48]);
+        plt.sh_addr = 0x1000;
+        plt.sh_entsize = entsize;
+
+        // DYN, x86-64
+        let data = ElfBuilder::new(3, 62).section(rela).section(plt).build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let plt = PltEntries::new(&sections, 62, &mut reader).unwrap();
+
+        plt.entries.iter().map(|entry| entry.0).collect()
+    }
+
+    #[test]
+    fn plt_stub_addresses() {
+        // the first .plt entry is reserved, .plt.sec has no such entry
+        assert_eq!(plt_stubs(".plt", 16), vec![Some(0x1010), Some(0x1020)]);
+        assert_eq!(plt_stubs(".plt.sec", 16), vec![Some(0x1000), Some(0x1010)]);
+        assert_eq!(plt_stubs(".plt", 8), vec![Some(0x1008), Some(0x1010)]);
+        // the stub size is unknown
+        assert_eq!(plt_stubs(".plt", 0), vec![None, None]);
+    }
+}
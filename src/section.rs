@@ -1,7 +1,38 @@
+use crate::error::ElfError;
 use crate::file::ElfFileHeader;
-use crate::reader::{LittleEndian, ReadBytesExt, Reader, Seek, SeekFrom};
-use crate::symbols::StringTable;
+use crate::json;
+use crate::program::{ProgramHeaders, SegmentType};
+use crate::reader::{LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
+use crate::symbols::{truncated_name, StringTable};
+use std::cmp::Reverse;
 use std::fmt;
+use std::str::FromStr;
+
+// Section contains writable data
+pub const SHF_WRITE: u64 = 1 << 0;
+// Section occupies memory during execution
+pub const SHF_ALLOC: u64 = 1 << 1;
+// Section contains executable instructions
+pub const SHF_EXECINSTR: u64 = 1 << 2;
+// `sh_info' holds a section header table index
+pub const SHF_INFO_LINK: u64 = 1 << 6;
+// Section holds thread-local data
+pub const SHF_TLS: u64 = 1 << 10;
+// Section data starts with compression header
+pub const SHF_COMPRESSED: u64 = 1 << 11;
+
+// Size of the ELF64 section header
+const SHDR_SIZE: u64 = 64;
+
+/* sizeof(Elf64_Chdr)
+ * typedef struct {
+ *    Elf64_Word  ch_type;
+ *    Elf64_Word  ch_reserved;
+ *    Elf64_Xword ch_size;
+ *    Elf64_Xword ch_addralign;
+ * } Elf64_Chdr;
+ */
+const COMPRESSION_HEADER_SIZE: u64 = 24;
 
 // XXX: use something like bitset
 fn sh_flags(value: u64) -> String {
@@ -16,7 +47,7 @@ fn sh_flags(value: u64) -> String {
     // Writable
     matchflag(1 << 0, 'W');
     // Occupies memory during execution
-    matchflag(1 << 1, 'A');
+    matchflag(SHF_ALLOC, 'A');
     // Executable
     matchflag(1 << 2, 'E');
     // Might be merged
@@ -24,7 +55,7 @@ fn sh_flags(value: u64) -> String {
     // Strings
     matchflag(1 << 5, 'S');
     // `sh_info' contains SHT index
-    matchflag(1 << 6, 'I');
+    matchflag(SHF_INFO_LINK, 'I');
     // Preserve order after combining
     matchflag(1 << 7, 'L');
     // Non-standard OS specific handling
@@ -32,9 +63,9 @@ fn sh_flags(value: u64) -> String {
     // Section is member of group
     matchflag(1 << 9, 'G');
     // Section hold thread-local data
-    matchflag(1 << 10, 'T');
+    matchflag(SHF_TLS, 'T');
     // Section with compressed data
-    matchflag(1 << 11, 'C');
+    matchflag(SHF_COMPRESSED, 'C');
 
     flags
 }
@@ -97,6 +128,8 @@ pub enum SectionHeaderType {
     Group,
     // Extended section indeces
     SymtabShndx,
+    // Compact relative relocation entries
+    Relr,
     // Object attributes
     GnuAttributes,
     // Gnu-style hash table
@@ -111,6 +144,43 @@ pub enum SectionHeaderType {
     GnuVerNeed,
     // Version symbol table
     GnuVerSym,
+    // Android packed relocation entries
+    AndroidRel,
+    AndroidRela,
+    // Android packed relative relocation entries
+    AndroidRelr,
+    // Incremental build data
+    GnuIncrementalInputs,
+    // LLVM ODR table
+    LlvmOdrTab,
+    // Linker options
+    LlvmLinkerOptions,
+    // Address-significance table
+    LlvmAddrSig,
+    // Dependent libraries
+    LlvmDependentLibraries,
+    // Symbol partition specification
+    LlvmSymPart,
+    // ELF header for loadable partition
+    LlvmPartEhdr,
+    // Program headers for loadable partition
+    LlvmPartPhdr,
+    // Basic block address map (legacy version)
+    LlvmBbAddrMapV0,
+    // Call graph profile
+    LlvmCallGraphProfile,
+    // Basic block address map
+    LlvmBbAddrMap,
+    // Offloading data
+    LlvmOffloading,
+    // Embedded LTO bitcode
+    LlvmLto,
+    // Sun-specific move table
+    SunwMove,
+    // Sun-specific COMDAT section
+    SunwComdat,
+    // Sun-specific symbol information
+    SunwSymInfo,
     Unknown(u32),
 }
 
@@ -118,21 +188,115 @@ pub enum SectionHeaderType {
 pub struct SectionHeaders {
     pub headers: Vec<SectionHeader>,
     pub strtab: StringTable,
+    // Longer names are cut in the listing
+    width: Option<usize>,
+    // Only sections occupying memory at run time are listed
+    alloc_only: bool,
+    // Uncompressed size of each SHF_COMPRESSED section from its
+    // compression header
+    uncompressed: Vec<Option<u64>>,
+}
+
+// Key used to order the section header listing
+#[derive(Debug, Clone, Copy)]
+pub enum SectionOrder {
+    // Virtual address, non-alloc sections go last
+    Address,
+    Offset,
+    // Largest sections first
+    Size,
+    Name,
+}
+
+// Section headers listed in a different order than in the file, the
+// original index is kept in the `[No]' column
+#[derive(Debug)]
+pub struct SortedSectionHeaders<'a> {
+    headers: &'a SectionHeaders,
+    order: Vec<usize>,
+    // Explain `sh_link' and `sh_info' of each section
+    verbose: bool,
+}
+
+// Number of sections and their total size for each section type
+#[derive(Debug)]
+pub struct SectionTypeSummary {
+    data: Vec<(SectionHeaderType, usize, u64)>,
+}
+
+// Total size of code, initialized and uninitialized data in the
+// Berkeley format of `size'
+#[derive(Debug)]
+pub struct SectionSizes {
+    text: u64,
+    data: u64,
+    bss: u64,
+    file: String,
+}
+
+// Thread-local storage each thread needs, from the TLS sections and
+// the TLS segment
+#[derive(Debug)]
+pub struct TlsSize {
+    // Name, size and whether the section is zero-initialized
+    sections: Vec<(String, u64, bool)>,
+    // Memory covered by the sections including alignment padding
+    span: u64,
+    // Memory size of the PT_TLS segment
+    segment: Option<u64>,
+}
+
+// Entry size defined by the ABI for sections holding tables
+pub fn default_entsize(sh_type: &SectionHeaderType, addrsize: u8) -> Option<u64> {
+    use SectionHeaderType::*;
+
+    let size = match (sh_type, addrsize) {
+        // Elf64_Sym, Elf32_Sym
+        (Symtab, 8) | (DynSym, 8) => 24,
+        (Symtab, _) | (DynSym, _) => 16,
+        // Elf64_Rela, Elf32_Rela
+        (Rela, 8) => 24,
+        (Rela, _) => 12,
+        // Elf64_Rel and Elf64_Dyn, Elf32_Rel and Elf32_Dyn
+        (Rel, 8) | (Dynamic, 8) => 16,
+        (Rel, _) | (Dynamic, _) => 8,
+        _ => return None,
+    };
+
+    Some(size)
 }
 
 impl SectionHeader {
-    fn new(reader: &mut Reader) -> SectionHeader {
-        SectionHeader {
-            sh_name: reader.read_u32::<LittleEndian>().unwrap(),
-            sh_type: SectionHeaderType::new(reader.read_u32::<LittleEndian>().unwrap()),
-            sh_flags: reader.read_u64::<LittleEndian>().unwrap(),
-            sh_addr: reader.read_u64::<LittleEndian>().unwrap(),
-            sh_offset: reader.read_u64::<LittleEndian>().unwrap(),
-            sh_size: reader.read_u64::<LittleEndian>().unwrap(),
-            sh_link: reader.read_u32::<LittleEndian>().unwrap(),
-            sh_info: reader.read_u32::<LittleEndian>().unwrap(),
-            sh_addralign: reader.read_u64::<LittleEndian>().unwrap(),
-            sh_entsize: reader.read_u64::<LittleEndian>().unwrap(),
+    fn new(reader: &mut Reader) -> Result<SectionHeader, ElfError> {
+        Ok(SectionHeader {
+            sh_name: reader.read_u32::<LittleEndian>()?,
+            sh_type: SectionHeaderType::new(reader.read_u32::<LittleEndian>()?),
+            sh_flags: reader.read_u64::<LittleEndian>()?,
+            sh_addr: reader.read_u64::<LittleEndian>()?,
+            sh_offset: reader.read_u64::<LittleEndian>()?,
+            sh_size: reader.read_u64::<LittleEndian>()?,
+            sh_link: reader.read_u32::<LittleEndian>()?,
+            sh_info: reader.read_u32::<LittleEndian>()?,
+            sh_addralign: reader.read_u64::<LittleEndian>()?,
+            sh_entsize: reader.read_u64::<LittleEndian>()?,
+        })
+    }
+
+    // Size of the table entries, some tools leave `sh_entsize' zero so
+    // the ABI size is used instead (section headers are read as ELF64)
+    pub fn entsize(&self) -> u64 {
+        match self.sh_entsize {
+            0 => default_entsize(&self.sh_type, 8).unwrap_or(0),
+            size => size,
+        }
+    }
+
+    // Number of bytes of the section in the file, NOBITS sections have
+    // only memory size and their offset may point anywhere
+    pub fn file_size(&self) -> u64 {
+        match self.sh_type {
+            SectionHeaderType::Bss => 0,
+            _ => self.sh_size,
         }
     }
 }
@@ -158,6 +322,7 @@ impl SectionHeaderType {
             16 => PreInitArray,
             17 => Group,
             18 => SymtabShndx,
+            19 => Relr,
             0x6ffffff5 => GnuAttributes,
             0x6ffffff6 => GnuHash,
             0x6ffffff7 => GnuLibList,
@@ -165,32 +330,93 @@ impl SectionHeaderType {
             0x6ffffffd => GnuVerDef,
             0x6ffffffe => GnuVerNeed,
             0x6fffffff => GnuVerSym,
+            0x60000001 => AndroidRel,
+            0x60000002 => AndroidRela,
+            0x6fffff00 => AndroidRelr,
+            0x6fff4700 => GnuIncrementalInputs,
+            0x6fff4c00 => LlvmOdrTab,
+            0x6fff4c01 => LlvmLinkerOptions,
+            // NOTE: 0x6fff4c02 was used for call graph profile before it
+            //       got its final value
+            0x6fff4c02 => LlvmCallGraphProfile,
+            0x6fff4c03 => LlvmAddrSig,
+            0x6fff4c04 => LlvmDependentLibraries,
+            0x6fff4c05 => LlvmSymPart,
+            0x6fff4c06 => LlvmPartEhdr,
+            0x6fff4c07 => LlvmPartPhdr,
+            0x6fff4c08 => LlvmBbAddrMapV0,
+            0x6fff4c09 => LlvmCallGraphProfile,
+            0x6fff4c0a => LlvmBbAddrMap,
+            0x6fff4c0b => LlvmOffloading,
+            0x6fff4c0c => LlvmLto,
+            0x6ffffffa => SunwMove,
+            0x6ffffffb => SunwComdat,
+            0x6ffffffc => SunwSymInfo,
             _ => Unknown(value),
         }
     }
 }
 
-impl SectionHeaders {
-    pub fn new(header: &ElfFileHeader, mut reader: &mut Reader) -> SectionHeaders {
-        reader.seek(SeekFrom::Start(header.e_shoff)).unwrap();
+// Read ch_size from the compression header of SHF_COMPRESSED section,
+// only the header is read, not the compressed data
+fn uncompressed_size(header: &SectionHeader, reader: &mut Reader) -> Option<u64> {
+    if header.sh_flags & SHF_COMPRESSED == 0 || header.sh_size < COMPRESSION_HEADER_SIZE {
+        return None;
+    }
+
+    // ch_size follows ch_type and ch_reserved
+    reader
+        .check_range(header.sh_offset, COMPRESSION_HEADER_SIZE)
+        .ok()?;
+    reader.seek(SeekFrom::Start(header.sh_offset + 8)).ok()?;
+    reader.read_u64::<LittleEndian>().ok()
+}
 
+impl SectionHeaders {
+    pub fn new(header: &ElfFileHeader, reader: &mut Reader) -> Result<SectionHeaders, ElfError> {
         let mut headers: Vec<SectionHeader> = vec![];
-        let mut section_no: u16 = 0;
 
-        while section_no < header.e_shnum {
-            headers.push(SectionHeader::new(&mut reader));
-            section_no += 1;
-        }
+        // read only headers present in the file
+        let size = reader.available(header.e_shoff, header.e_shnum as u64 * SHDR_SIZE);
 
-        let strtab: StringTable;
+        reader.seek(SeekFrom::Start(header.e_shoff))?;
 
-        if header.e_shnum > 0 {
-            strtab = StringTable::new(&headers[header.e_shstrndx as usize], &mut reader);
-        } else {
-            strtab = StringTable::empty();
+        for _ in 0..size / SHDR_SIZE {
+            headers.push(SectionHeader::new(reader)?);
         }
 
-        SectionHeaders { headers, strtab }
+        // invalid index leaves section names unresolved instead of failing
+        let strtab = match headers.get(header.e_shstrndx as usize) {
+            Some(strtab) => StringTable::new_partial(strtab, reader)?,
+            None => StringTable::empty(),
+        };
+
+        let uncompressed = headers
+            .iter()
+            .map(|header| uncompressed_size(header, reader))
+            .collect();
+
+        Ok(SectionHeaders {
+            headers,
+            strtab,
+            width: None,
+            alloc_only: false,
+            uncompressed,
+        })
+    }
+
+    pub fn set_width(&mut self, width: Option<usize>) {
+        self.width = width;
+    }
+
+    pub fn set_alloc_only(&mut self, alloc_only: bool) {
+        self.alloc_only = alloc_only;
+    }
+
+    // Size of the section data after decompression, None for sections
+    // that are not compressed
+    pub fn uncompressed_size(&self, index: usize) -> Option<u64> {
+        self.uncompressed.get(index).cloned().flatten()
     }
 
     pub fn get_all(&self, header_type: SectionHeaderType) -> Vec<SectionHeader> {
@@ -213,7 +439,60 @@ impl SectionHeaders {
         self.headers[index].clone()
     }
 
-    pub fn dynstr(&self, reader: &mut Reader) -> Option<StringTable> {
+    pub fn try_get_by_index(&self, index: usize) -> Result<SectionHeader, ElfError> {
+        self.headers
+            .get(index)
+            .cloned()
+            .ok_or(ElfError::InvalidSectionIndex(index))
+    }
+
+    // Name of the section referenced by `sh_info', relocation sections
+    // use it as an index even without SHF_INFO_LINK set, zero means none
+    pub fn info_link_name(&self, header: &SectionHeader) -> Option<String> {
+        let is_reloc =
+            header.sh_type == SectionHeaderType::Rel || header.sh_type == SectionHeaderType::Rela;
+
+        if header.sh_info == 0 || !(is_reloc || header.sh_flags & SHF_INFO_LINK != 0) {
+            return None;
+        }
+
+        Some(match self.headers.get(header.sh_info as usize) {
+            Some(target) => self.strtab.get(target.sh_name as u64),
+            None => format!("<bad section index {}>", header.sh_info),
+        })
+    }
+
+    // Table sections with zero `sh_entsize' for which the ABI size is used
+    pub fn entsize_warnings(&self) -> Vec<String> {
+        self.headers
+            .iter()
+            .filter(|header| header.sh_entsize == 0 && header.entsize() != 0)
+            .map(|header| {
+                format!(
+                    "Section `{}' has zero sh_entsize, using {}",
+                    self.strtab.get(header.sh_name as u64),
+                    header.entsize()
+                )
+            })
+            .collect()
+    }
+
+    // Allocated section starting at the virtual address
+    pub fn get_by_address(&self, addr: u64) -> Option<SectionHeader> {
+        self.headers
+            .iter()
+            .find(|header| header.sh_addr == addr && header.sh_flags & SHF_ALLOC != 0)
+            .cloned()
+    }
+
+    pub fn get_by_name(&self, name: &str) -> Option<SectionHeader> {
+        self.headers
+            .iter()
+            .find(|header| self.strtab.get(header.sh_name as u64) == name)
+            .cloned()
+    }
+
+    pub fn dynstr(&self, reader: &mut Reader) -> Result<Option<StringTable>, ElfError> {
         for header in &self.headers {
             if header.sh_type != SectionHeaderType::Strtab {
                 continue;
@@ -225,15 +504,315 @@ impl SectionHeaders {
                 continue;
             }
 
-            return Some(StringTable::new(header, reader));
+            return StringTable::new(header, reader).map(Some);
+        }
+
+        Ok(None)
+    }
+}
+
+impl FromStr for SectionOrder {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<SectionOrder, String> {
+        match value {
+            "addr" => Ok(SectionOrder::Address),
+            "offset" => Ok(SectionOrder::Offset),
+            "size" => Ok(SectionOrder::Size),
+            "name" => Ok(SectionOrder::Name),
+            _ => Err(format!(
+                "invalid order: {}, expected addr, offset, size or name",
+                value
+            )),
+        }
+    }
+}
+
+impl<'a> SortedSectionHeaders<'a> {
+    // Headers are kept in the file order when no order is given
+    pub fn new(
+        headers: &'a SectionHeaders,
+        order: Option<SectionOrder>,
+        verbose: bool,
+    ) -> SortedSectionHeaders<'a> {
+        let mut indices: Vec<usize> = (0..headers.headers.len()).collect();
+        let all = &headers.headers;
+
+        match order {
+            None => {}
+            Some(SectionOrder::Address) => {
+                indices.sort_by_key(|i| (all[*i].sh_addr == 0, all[*i].sh_addr))
+            }
+            Some(SectionOrder::Offset) => indices.sort_by_key(|i| all[*i].sh_offset),
+            Some(SectionOrder::Size) => indices.sort_by_key(|i| Reverse(all[*i].sh_size)),
+            Some(SectionOrder::Name) => {
+                indices.sort_by_key(|i| headers.strtab.get(all[*i].sh_name as u64))
+            }
+        }
+
+        SortedSectionHeaders {
+            headers,
+            order: indices,
+            verbose,
+        }
+    }
+}
+
+impl fmt::Display for SortedSectionHeaders<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.headers.fmt_headers(f, &self.order, self.verbose)
+    }
+}
+
+impl SectionTypeSummary {
+    pub fn new(headers: &SectionHeaders) -> SectionTypeSummary {
+        let mut data: Vec<(SectionHeaderType, usize, u64)> = vec![];
+
+        for header in &headers.headers {
+            match data.iter_mut().find(|(kind, _, _)| *kind == header.sh_type) {
+                Some((_, count, size)) => {
+                    *count += 1;
+                    *size = size.saturating_add(header.sh_size);
+                }
+                None => data.push((header.sh_type.clone(), 1, header.sh_size)),
+            }
+        }
+
+        data.sort_by_key(|(_, _, size)| Reverse(*size));
+
+        SectionTypeSummary { data }
+    }
+}
+
+impl SectionSizes {
+    // Same as `size', read-only data are counted as text
+    pub fn new(headers: &SectionHeaders, file: String) -> SectionSizes {
+        let mut sizes = SectionSizes {
+            text: 0,
+            data: 0,
+            bss: 0,
+            file,
+        };
+
+        for header in &headers.headers {
+            if header.sh_flags & SHF_ALLOC == 0 {
+                continue;
+            }
+
+            let size = if header.sh_type == SectionHeaderType::Bss {
+                &mut sizes.bss
+            } else if header.sh_flags & (SHF_WRITE | SHF_EXECINSTR) == SHF_WRITE {
+                &mut sizes.data
+            } else {
+                &mut sizes.text
+            };
+
+            *size = size.saturating_add(header.sh_size);
+        }
+
+        sizes
+    }
+}
+
+impl TlsSize {
+    pub fn new(headers: &SectionHeaders, prheaders: &ProgramHeaders) -> TlsSize {
+        let tls: Vec<&SectionHeader> = headers
+            .headers
+            .iter()
+            .filter(|header| header.sh_flags & SHF_TLS != 0)
+            .collect();
+
+        let start = tls.iter().map(|header| header.sh_addr).min();
+        let end = tls
+            .iter()
+            .map(|header| header.sh_addr.saturating_add(header.sh_size))
+            .max();
+
+        TlsSize {
+            sections: tls
+                .iter()
+                .map(|header| {
+                    (
+                        headers.strtab.get(header.sh_name as u64),
+                        header.sh_size,
+                        header.sh_type == SectionHeaderType::Bss,
+                    )
+                })
+                .collect(),
+            span: match (start, end) {
+                (Some(start), Some(end)) => end - start,
+                _ => 0,
+            },
+            segment: prheaders
+                .get_all(SegmentType::ThreadLocalStorage)
+                .first()
+                .map(|header| header.p_memsiz),
+        }
+    }
+}
+
+impl fmt::Display for TlsSize {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.sections.is_empty() && self.segment.is_none() {
+            return writeln!(f, "There is no thread-local storage");
+        }
+
+        writeln!(f, "Thread-local storage:")?;
+
+        for (name, size, zero) in &self.sections {
+            let kind = if *zero { "zero" } else { "initialized" };
+            writeln!(f, "{:<16} {:#010x} {}", name, size, kind)?;
+        }
+
+        let total = self
+            .sections
+            .iter()
+            .fold(0u64, |total, (_, size, _)| total.saturating_add(*size));
+        writeln!(f, "{:<16} {:#010x}", "Sections total", total)?;
+
+        match self.segment {
+            Some(size) => {
+                writeln!(f, "{:<16} {:#010x}", "TLS segment", size)?;
+
+                // the segment also covers padding between the sections
+                if !self.sections.is_empty() && size != self.span {
+                    writeln!(
+                        f,
+                        "Warning: TLS sections cover {:#x} bytes, TLS segment {:#x} bytes",
+                        self.span, size
+                    )?;
+                }
+            }
+            None => writeln!(f, "There is no TLS segment")?,
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for SectionSizes {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.text.saturating_add(self.data).saturating_add(self.bss);
+
+        writeln!(
+            f,
+            "{:>7}\t{:>7}\t{:>7}\t{:>7}\t{:>7}\tfilename",
+            "text", "data", "bss", "dec", "hex"
+        )?;
+        writeln!(
+            f,
+            "{:>7}\t{:>7}\t{:>7}\t{:>7}\t{:>7x}\t{}",
+            self.text, self.data, self.bss, total, total, self.file
+        )
+    }
+}
+
+impl fmt::Display for SectionTypeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Sections by type:")?;
+        writeln!(f, "{:<24} {:<8} Size", "Type", "Count")?;
+
+        for (kind, count, size) in &self.data {
+            writeln!(f, "{:<24} {:<8} {:#x}", kind.to_string(), count, size)?;
         }
+        Ok(())
+    }
+}
 
-        None
+impl fmt::Display for SectionHeaderType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SectionHeaderType::Unknown(value) => write!(f, "Unknown({:#x})", value),
+            _ => write!(f, "{:?}", self),
+        }
     }
 }
 
 impl fmt::Display for SectionHeaders {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let order: Vec<usize> = (0..self.headers.len()).collect();
+        self.fmt_headers(f, &order, false)
+    }
+}
+
+impl SectionHeaders {
+    pub fn to_json(&self) -> String {
+        let headers: Vec<String> = self
+            .headers
+            .iter()
+            .map(|header| {
+                json::object(&[
+                    (
+                        "name",
+                        json::string(&self.strtab.get(header.sh_name as u64)),
+                    ),
+                    ("type", json::string(&header.sh_type.to_string())),
+                    ("flags", json::hex(header.sh_flags)),
+                    ("addr", json::hex(header.sh_addr)),
+                    ("offset", json::hex(header.sh_offset)),
+                    ("size", json::hex(header.sh_size)),
+                    ("link", header.sh_link.to_string()),
+                    ("info", header.sh_info.to_string()),
+                    ("align", json::hex(header.sh_addralign)),
+                    ("entsize", json::hex(header.sh_entsize)),
+                ])
+            })
+            .collect();
+
+        json::array(&headers)
+    }
+
+    // `[N] name' of the section with the given index
+    fn link_name(&self, index: u32) -> String {
+        match self.headers.get(index as usize) {
+            Some(header) => format!("[{}] {}", index, self.strtab.get(header.sh_name as u64)),
+            None => format!("[{}] <bad section index>", index),
+        }
+    }
+
+    // Meaning of `sh_link' and `sh_info', it depends on the section type
+    fn link_info(&self, header: &SectionHeader) -> Vec<String> {
+        use SectionHeaderType::*;
+
+        let link = self.link_name(header.sh_link);
+        let info = self.link_name(header.sh_info);
+
+        let mut result = match header.sh_type {
+            Symtab | DynSym => vec![
+                format!("string table {}", link),
+                format!("first non-local symbol {}", header.sh_info),
+            ],
+            // dynamic relocations don't apply to a single section
+            Rel | Rela if header.sh_info == 0 => vec![format!("symbol table {}", link)],
+            Rel | Rela => vec![
+                format!("symbol table {}", link),
+                format!("relocated section {}", info),
+            ],
+            Dynamic | GnuVerDef | GnuVerNeed => vec![format!("string table {}", link)],
+            Hash | GnuHash | GnuVerSym | SymtabShndx => vec![format!("symbol table {}", link)],
+            Group => vec![
+                format!("symbol table {}", link),
+                format!("signature symbol {}", header.sh_info),
+            ],
+            _ if header.sh_link != 0 => vec![format!("linked section {}", link)],
+            _ => vec![],
+        };
+
+        match header.sh_type {
+            GnuVerDef => result.push(format!("{} version definitions", header.sh_info)),
+            GnuVerNeed => result.push(format!("{} needed files", header.sh_info)),
+            Symtab | DynSym | Rel | Rela | Group => {}
+            _ if header.sh_flags & SHF_INFO_LINK != 0 => {
+                result.push(format!("info section {}", info))
+            }
+            _ => {}
+        }
+
+        result
+    }
+
+    // Print headers with the given indices in the given order
+    fn fmt_headers(&self, f: &mut fmt::Formatter, order: &[usize], verbose: bool) -> fmt::Result {
         writeln!(f, "Section headers:")?;
         writeln!(
             f,
@@ -246,15 +825,20 @@ impl fmt::Display for SectionHeaders {
             "Size", "EntSize", "Flags", "Align"
         )?;
 
-        for (i, header) in self.headers.iter().enumerate() {
+        for i in order {
+            let header = &self.headers[*i];
             let name = self.strtab.get(header.sh_name as u64);
 
+            if self.alloc_only && header.sh_flags & SHF_ALLOC == 0 {
+                continue;
+            }
+
             writeln!(
                 f,
                 "[{:02}] {:16} {:<16} {:#016x} {:#08x}",
                 i,
-                name,
-                format!("{:?}", header.sh_type),
+                truncated_name(&name, self.width),
+                header.sh_type.to_string(),
                 header.sh_addr,
                 header.sh_offset
             )?;
@@ -268,8 +852,74 @@ impl fmt::Display for SectionHeaders {
                 header.sh_info,
                 header.sh_addralign
             )?;
+
+            if let Some(size) = self.uncompressed_size(*i) {
+                // share of the uncompressed size the section takes in the file
+                let ratio = match size {
+                    0 => 0,
+                    _ => header.sh_size.saturating_mul(100) / size,
+                };
+
+                writeln!(f, "     compressed from {:#x} bytes ({}%)", size, ratio)?;
+            }
+
+            if verbose {
+                for line in self.link_info(header) {
+                    writeln!(f, "     {}", line)?;
+                }
+            }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, symbol, ElfBuilder, Section};
+    use crate::symbols::SymbolTables;
+
+    // SHT_SYMTAB, SHT_STRTAB
+    const SYMTAB: u32 = 2;
+    const STRTAB: u32 = 3;
+
+    #[test]
+    fn zero_entsize() {
+        let mut data = symbol(0, 0, 0, 0, 0);
+        data.extend(symbol(1, 0x12, 1, 0x1000, 16));
+
+        // sh_entsize is left zero
+        let mut symtab = Section::new(".symtab", SYMTAB, data);
+        symtab.sh_link = 2;
+
+        // REL, x86-64
+        let data = ElfBuilder::new(1, 62)
+            .section(symtab)
+            .section(Section::new(".strtab", STRTAB, b"\0main\0".to_vec()))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+
+        assert_eq!(sections.headers[1].entsize(), 24);
+        assert_eq!(sections.headers[2].entsize(), 0);
+        assert_eq!(
+            sections.entsize_warnings(),
+            vec!["Section `.symtab' has zero sh_entsize, using 24"]
+        );
+
+        let symbols = SymbolTables::new(&sections, &header, &mut reader).unwrap();
+        assert_eq!(symbols.names(), vec!["", "main"]);
+    }
+
+    #[test]
+    fn default_entsize_by_class() {
+        use SectionHeaderType::*;
+
+        assert_eq!(default_entsize(&Rela, 8), Some(24));
+        assert_eq!(default_entsize(&Rela, 4), Some(12));
+        assert_eq!(default_entsize(&Dynamic, 4), Some(8));
+        assert_eq!(default_entsize(&Data, 8), None);
+    }
+}
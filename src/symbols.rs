@@ -1,7 +1,34 @@
-use crate::reader::{LittleEndian, ReadBytesExt, Reader, Seek, SeekFrom};
-use crate::section::{SectionHeader, SectionHeaderType, SectionHeaders};
+use crate::error::{ElfError, Result};
+use crate::file::{ElfFileHeader, ObjectType};
+use crate::json;
+use crate::program::ProgramHeaders;
+use crate::reader::{LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
+use crate::relocs::RelocationSections;
+use crate::section::{
+    SectionHeader, SectionHeaderType, SectionHeaders, SHF_ALLOC, SHF_EXECINSTR, SHF_WRITE,
+};
+use crate::version::{symbol_versions, VersionDefSection, VersionSection};
+use std::borrow::Cow;
+use std::collections::BTreeSet;
 use std::fmt;
-use std::io::Read;
+use std::io::{Read, Write};
+
+// Undefined section index
+const SHN_UNDEF: u16 = 0;
+// Start of reserved section indices (SHN_ABS, SHN_COMMON, ...)
+const SHN_LORESERVE: u16 = 0xff00;
+// Symbol has absolute value
+const SHN_ABS: u16 = 0xfff1;
+// Symbol labels unallocated common block
+const SHN_COMMON: u16 = 0xfff2;
+// Section index is stored in the SHT_SYMTAB_SHNDX section
+const SHN_XINDEX: u16 = 0xffff;
+
+// Size of the ELF64 symbol table entry
+const SYMBOL_SIZE: u64 = 24;
+
+const SYMBOL_TABLE_COLUMNS: &str =
+    "Num    Value            Size     Type     Bind   Vis       Ndx Name";
 
 #[derive(Debug)]
 pub struct StringTable {
@@ -14,12 +41,16 @@ pub struct StringTable {
 pub struct Symbol {
     // Symbol name (pub string tbl index)
     pub st_name: u32,
+    // Symbol type and binding, kept for values the enums do not decode
+    pub st_info: u8,
     // Symbol type
     pub st_type: SymbolType,
     // Symbol binding
     pub st_bind: SymbolBinding,
     // Symbol visibility
     pub st_vis: SymbolVisibility,
+    // Symbol visibility and processor specific bits
+    pub st_other: u8,
     // Section index
     pub st_shndx: u16,
     // Symbol value
@@ -80,7 +111,45 @@ pub struct SymbolTable {
     data: Vec<Symbol>,
     strtab: StringTable,
     name: String,
-    symsize: usize,
+    machine: u16,
+    // Section names, set only for relocatable objects where symbol
+    // values are offsets within the section
+    sections: Vec<String>,
+    // Extended section indices from SHT_SYMTAB_SHNDX, one per symbol
+    shndx: Vec<u32>,
+    // Location and resolver address of IRELATIVE relocations, set only
+    // when relocations were parsed too
+    irelative: Vec<(u64, u64)>,
+    // File ended before the end of the table
+    truncated: bool,
+    // Only symbols matching the filter are listed
+    filter: Option<SymbolFilter>,
+    // Longer names are cut in the listing
+    width: Option<usize>,
+    // Rust and C++ names are demangled in the listing
+    demangle: bool,
+}
+
+// Which symbols of the table are listed
+#[derive(Debug, Clone, Copy)]
+pub enum SymbolFilter {
+    // References to symbols from other modules
+    Undefined,
+    Defined,
+}
+
+// Single line of the symbol table listing
+struct SymbolRow<'a> {
+    index: usize,
+    name: &'a str,
+    sym: &'a Symbol,
+    machine: u16,
+    // Section index, resolved for SHN_XINDEX symbols
+    shndx: u32,
+    // Section the value is relative to
+    section: Option<&'a str>,
+    // Annotation printed after the name
+    note: &'a str,
 }
 
 #[derive(Debug)]
@@ -88,64 +157,220 @@ pub struct SymbolTables {
     data: Vec<SymbolTable>,
 }
 
+// Symbols from the dynamic symbol table that make up the public
+// interface of a shared object
+#[derive(Debug)]
+pub struct ExportedSymbols {
+    data: Vec<(String, Symbol)>,
+    demangle: bool,
+}
+
 impl StringTable {
     // XXX: use some kind of buffer for this
     pub fn get(&self, offset: u64) -> String {
-        let sub = &self.buffer[offset as usize..];
-        let mut result = String::new();
+        self.get_str(offset).into_owned()
+    }
 
-        for ch in sub.iter() {
-            if *ch != 0 {
-                result.push(*ch as char);
-            } else {
-                break;
+    // Same as `get', but the string is borrowed from the table unless it
+    // contains invalid UTF-8 sequences
+    pub fn get_str(&self, offset: u64) -> Cow<'_, str> {
+        // corrupted files may point past the end of the table
+        match self.buffer.get(offset as usize..) {
+            Some(sub) => {
+                let end = sub.iter().position(|byte| *byte == 0).unwrap_or(sub.len());
+                String::from_utf8_lossy(&sub[..end])
             }
+            None => Cow::Borrowed("<bad offset>"),
         }
+    }
 
-        result
+    pub fn try_get(&self, offset: u64) -> Result<String> {
+        if offset as usize >= self.buffer.len() && offset != 0 {
+            return Err(ElfError::BadStringOffset(offset));
+        }
+
+        Ok(self.get(offset))
     }
 
     pub fn empty() -> StringTable {
         StringTable { buffer: vec![] }
     }
 
-    pub fn new(hdr: &SectionHeader, reader: &mut Reader) -> StringTable {
-        reader.seek(SeekFrom::Start(hdr.sh_offset)).unwrap();
+    pub fn new(hdr: &SectionHeader, reader: &mut Reader) -> Result<StringTable> {
+        StringTable::read(hdr.sh_offset, hdr.file_size(), reader)
+    }
+
+    // Read only the part of the table present in the file, names past the
+    // end of the file are left unresolved
+    pub fn new_partial(hdr: &SectionHeader, reader: &mut Reader) -> Result<StringTable> {
+        match reader.available(hdr.sh_offset, hdr.file_size()) {
+            0 => Ok(StringTable::empty()),
+            size => StringTable::read(hdr.sh_offset, size, reader),
+        }
+    }
+
+    // Read string table that is not described by a section header
+    pub fn read(offset: u64, size: u64, reader: &mut Reader) -> Result<StringTable> {
+        reader.check_range(offset, size)?;
+        reader.seek(SeekFrom::Start(offset))?;
+
+        let mut buffer = vec![0; size as usize];
+        reader.read_exact(&mut buffer)?;
+
+        Ok(StringTable { buffer })
+    }
+}
+
+// Demangled Rust or C++ symbol name, the name is returned unchanged when
+// it is not mangled or when the crate is built without the `demangle'
+// feature
+#[cfg(feature = "demangle")]
+fn demangle(name: Cow<'_, str>) -> Cow<'_, str> {
+    if let Ok(demangled) = rustc_demangle::try_demangle(&name) {
+        // alternate format omits the hash suffix
+        return Cow::Owned(format!("{:#}", demangled));
+    }
+
+    match cpp_demangle::Symbol::new(name.as_ref())
+        .map(|symbol| symbol.demangle(&Default::default()))
+    {
+        Ok(Ok(demangled)) => Cow::Owned(demangled),
+        _ => name,
+    }
+}
+
+#[cfg(not(feature = "demangle"))]
+fn demangle(name: Cow<'_, str>) -> Cow<'_, str> {
+    name
+}
 
-        let mut handle = reader.take(hdr.sh_size);
-        let mut buffer: Vec<u8> = Vec::new();
+// Name cut to the first `width' characters followed by `...', the name is
+// borrowed when it fits or when there is no width limit
+pub fn truncated_name(name: &str, width: Option<usize>) -> Cow<'_, str> {
+    match width.and_then(|width| name.char_indices().nth(width)) {
+        Some((end, _)) => Cow::Owned(format!("{}...", &name[..end])),
+        None => Cow::Borrowed(name),
+    }
+}
 
-        handle.read_to_end(&mut buffer).unwrap();
+// Entry size of the symbol table, smaller entries would overlap and zero
+// size would never advance to the next one
+fn check_entsize(header: &SectionHeader) -> Result<u64> {
+    match header.entsize() {
+        size if size < SYMBOL_SIZE => Err(ElfError::Malformed(format!(
+            "symbol table entry size {}",
+            size
+        ))),
+        size => Ok(size),
+    }
+}
 
-        StringTable { buffer }
+// Names of the sections the symbol values are relative to, only symbols
+// of relocatable files have section-relative values
+fn section_names(headers: &SectionHeaders, file: &ElfFileHeader) -> Vec<String> {
+    match file.e_type {
+        ObjectType::RelocatableFile => headers
+            .headers
+            .iter()
+            .map(|header| headers.strtab.get(header.sh_name as u64))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+// Name of the section the symbol value is relative to, `shndx' is the
+// resolved section index
+fn value_section<'a>(sections: &'a [String], sym: &Symbol, shndx: u32) -> Option<&'a str> {
+    match sym.st_shndx {
+        SHN_XINDEX => sections.get(shndx as usize).map(String::as_str),
+        SHN_UNDEF | SHN_LORESERVE..=0xffff => None,
+        _ => sections.get(shndx as usize).map(String::as_str),
+    }
+}
+
+// Section index of the symbol, `shndx' is its entry in the extended
+// section index table, if there is one
+fn resolve_shndx(sym: &Symbol, shndx: Option<&u32>) -> u32 {
+    match (sym.st_shndx, shndx) {
+        (SHN_XINDEX, Some(shndx)) => *shndx,
+        _ => sym.st_shndx as u32,
     }
 }
 
 impl Symbol {
-    pub fn new(reader: &mut Reader) -> Symbol {
-        let st_name = reader.read_u32::<LittleEndian>().unwrap();
+    pub fn new(reader: &mut Reader) -> Result<Symbol> {
+        let st_name = reader.read_u32::<LittleEndian>()?;
 
-        let st_info = reader.read_u8().unwrap();
+        let st_info = reader.read_u8()?;
         let st_type = SymbolType::new(st_info);
         let st_bind = SymbolBinding::new(st_info);
 
-        let st_other = reader.read_u8().unwrap();
+        let st_other = reader.read_u8()?;
         let st_vis = SymbolVisibility::new(st_other);
 
-        let st_shndx = reader.read_u16::<LittleEndian>().unwrap();
-        let st_value = reader.read_u64::<LittleEndian>().unwrap();
-        let st_size = reader.read_u64::<LittleEndian>().unwrap();
+        let st_shndx = reader.read_u16::<LittleEndian>()?;
+        let st_value = reader.read_u64::<LittleEndian>()?;
+        let st_size = reader.read_u64::<LittleEndian>()?;
 
-        Symbol {
+        Ok(Symbol {
             st_name,
+            st_info,
             st_type,
             st_bind,
             st_vis,
+            st_other,
             st_shndx,
             st_value,
             st_size,
+        })
+    }
+
+    // Reserved symbol with index 0, used in place of missing symbols
+    pub fn null() -> Symbol {
+        Symbol {
+            st_name: 0,
+            st_info: 0,
+            st_type: SymbolType::new(0),
+            st_bind: SymbolBinding::new(0),
+            st_vis: SymbolVisibility::new(0),
+            st_other: 0,
+            st_shndx: SHN_UNDEF,
+            st_value: 0,
+            st_size: 0,
         }
     }
+
+    pub fn is_defined(&self) -> bool {
+        self.st_shndx != SHN_UNDEF
+    }
+
+    // Name of the symbol in `strtab', demangled when it is a Rust or C++
+    // name, it is borrowed from the table when no demangling applies
+    pub fn demangled_name<'a>(&self, strtab: &'a StringTable) -> Cow<'a, str> {
+        demangle(strtab.get_str(self.st_name as u64))
+    }
+
+    // Defined non-local symbol visible from other modules
+    pub fn is_exported(&self) -> bool {
+        let global = matches!(
+            self.st_bind,
+            SymbolBinding::Global | SymbolBinding::Weak | SymbolBinding::GnuUnique
+        );
+        let visible = matches!(
+            self.st_vis,
+            SymbolVisibility::Default | SymbolVisibility::Protected
+        );
+
+        global && visible && self.is_defined()
+    }
+
+    // PPC64 ELFv2 stores offset between the global and local entry
+    // point in the top three bits of st_other
+    pub fn ppc64_local_entry_offset(&self) -> u64 {
+        let value = (self.st_other & 0xe0) >> 5;
+
+        ((1 << value) >> 2) << 2
+    }
 }
 
 impl SymbolType {
@@ -178,6 +403,16 @@ impl SymbolBinding {
             _ => Unknown(info >> 4),
         }
     }
+
+    // Global definitions win over weak ones, the same as when the
+    // linker resolves a name, local symbols come last
+    fn priority(&self) -> u8 {
+        match self {
+            SymbolBinding::Global | SymbolBinding::GnuUnique => 2,
+            SymbolBinding::Weak => 1,
+            _ => 0,
+        }
+    }
 }
 
 impl SymbolVisibility {
@@ -195,56 +430,871 @@ impl SymbolVisibility {
     }
 }
 
+impl SymbolFilter {
+    pub fn matches(&self, sym: &Symbol) -> bool {
+        match self {
+            SymbolFilter::Undefined => !sym.is_defined(),
+            SymbolFilter::Defined => sym.is_defined(),
+        }
+    }
+
+    fn describe(&self) -> &'static str {
+        match self {
+            SymbolFilter::Undefined => "undefined",
+            SymbolFilter::Defined => "defined",
+        }
+    }
+}
+
 impl SymbolTable {
     pub fn new(
         headers: &SectionHeaders,
         header: &SectionHeader,
-        mut reader: &mut Reader,
-    ) -> SymbolTable {
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<SymbolTable> {
         // XXX: check that header.sh_type is SHT_SYMTAB or SHT_DYNSYM
-        reader.seek(SeekFrom::Start(header.sh_offset)).unwrap();
-
+        let entsize = check_entsize(header)?;
         let mut data = vec![];
-        let mut i = 0;
 
-        // XXX: use some better method for checking the end
-        while i < header.sh_size {
-            i += header.sh_entsize;
-            data.push(Symbol::new(&mut reader));
+        // read only symbols present in the file
+        let size = reader.available(header.sh_offset, header.sh_size);
+
+        for i in 0..size / entsize {
+            reader.seek(SeekFrom::Start(header.sh_offset + i * entsize))?;
+            data.push(Symbol::new(reader)?);
         }
 
-        let strtab = &headers.headers[header.sh_link as usize];
+        // invalid link leaves names unresolved instead of failing
+        let strtab = match headers.headers.get(header.sh_link as usize) {
+            Some(strtab) => StringTable::new_partial(strtab, reader)?,
+            None => StringTable::empty(),
+        };
         let name = headers.strtab.get(header.sh_name as u64);
 
-        SymbolTable {
+        Ok(SymbolTable {
             data,
             name,
-            strtab: StringTable::new(&strtab, reader),
-            symsize: header.sh_entsize as usize,
+            strtab,
+            machine,
+            sections: vec![],
+            shndx: vec![],
+            irelative: vec![],
+            truncated: size < header.sh_size,
+            filter: None,
+            width: None,
+            demangle: false,
+        })
+    }
+
+    // Call `callback` for every symbol of the table while reading them one
+    // by one, the whole file is still read into memory, but the symbols
+    // are not decoded into a table
+    pub fn for_each<F>(
+        headers: &SectionHeaders,
+        header: &SectionHeader,
+        reader: &mut Reader,
+        mut callback: F,
+    ) -> Result<()>
+    where
+        F: FnMut(usize, &str, &Symbol) -> Result<()>,
+    {
+        let entsize = check_entsize(header)?;
+
+        // same as `SymbolTable::new', only symbols present in the file are
+        // read and an invalid link leaves names unresolved
+        let size = reader.available(header.sh_offset, header.sh_size);
+        let strtab = match headers.headers.get(header.sh_link as usize) {
+            Some(strtab) => StringTable::new_partial(strtab, reader)?,
+            None => StringTable::empty(),
+        };
+
+        for i in 0..size / entsize {
+            reader.seek(SeekFrom::Start(header.sh_offset + i * entsize))?;
+
+            let sym = Symbol::new(reader)?;
+            callback(i as usize, &strtab.get(sym.st_name as u64), &sym)?;
+        }
+
+        Ok(())
+    }
+
+    // For ifunc symbols `st_value' is the address of the resolver
+    fn ifunc_note(&self, resolver: u64) -> String {
+        let relocs: Vec<String> = self
+            .irelative
+            .iter()
+            .filter(|(_, addend)| *addend == resolver)
+            .map(|(offset, _)| format!("{:#x}", offset))
+            .collect();
+
+        match relocs.len() {
+            0 => String::from(" (ifunc resolver)"),
+            _ => format!(" (ifunc resolver, IRELATIVE at {})", relocs.join(", ")),
+        }
+    }
+
+    pub fn empty(machine: u16) -> SymbolTable {
+        SymbolTable {
+            data: vec![],
+            strtab: StringTable::empty(),
+            name: String::new(),
+            machine,
+            sections: vec![],
+            shndx: vec![],
+            irelative: vec![],
+            truncated: false,
+            filter: None,
+            width: None,
+            demangle: false,
+        }
+    }
+
+    // Section index of the symbol, symbols with SHN_XINDEX have the index
+    // stored in the extended section index table
+    pub fn section_index(&self, index: usize) -> u32 {
+        resolve_shndx(&self.data[index], self.shndx.get(index))
+    }
+
+    // Read SHT_SYMTAB_SHNDX section that belongs to the symbol table with
+    // the given index, toolchains may emit it even for small tables
+    fn read_shndx(headers: &SectionHeaders, index: usize, reader: &mut Reader) -> Result<Vec<u32>> {
+        let header = match headers.headers.iter().find(|header| {
+            header.sh_type == SectionHeaderType::SymtabShndx && header.sh_link as usize == index
+        }) {
+            Some(header) => header,
+            None => return Ok(vec![]),
+        };
+
+        reader.check_range(header.sh_offset, header.sh_size)?;
+        reader.seek(SeekFrom::Start(header.sh_offset))?;
+
+        let mut result = vec![];
+        for _ in 0..header.sh_size / 4 {
+            result.push(reader.read_u32::<LittleEndian>()?);
         }
+        Ok(result)
     }
 
     pub fn get_by_index(&self, index: usize) -> (String, Symbol) {
-        let sym = self.data.get(index).unwrap();
-        let name = self.strtab.get(sym.st_name as u64);
+        match self.data.get(index) {
+            Some(sym) => (self.strtab.get(sym.st_name as u64), sym.clone()),
+            None if index == 0 => (String::new(), Symbol::null()),
+            None => (format!("<bad index {}>", index), Symbol::null()),
+        }
+    }
 
-        (name, sym.clone())
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.data
+            .iter()
+            .map(|sym| self.strtab.get(sym.st_name as u64))
+            .collect()
     }
 }
 
 impl SymbolTables {
-    pub fn new(headers: &SectionHeaders, reader: &mut Reader) -> SymbolTables {
+    pub fn new(
+        headers: &SectionHeaders,
+        file: &ElfFileHeader,
+        reader: &mut Reader,
+    ) -> Result<SymbolTables> {
         let mut data: Vec<SymbolTable> = vec![];
 
-        for header in &headers.headers {
+        let sections = section_names(headers, file);
+
+        for (index, header) in headers.headers.iter().enumerate() {
             if header.sh_type == SectionHeaderType::DynSym
                 || header.sh_type == SectionHeaderType::Symtab
             {
-                data.push(SymbolTable::new(headers, &header, reader));
+                let mut symtab = SymbolTable::new(headers, header, file.e_machine, reader)?;
+                symtab.sections = sections.clone();
+                symtab.shndx = SymbolTable::read_shndx(headers, index, reader)?;
+                data.push(symtab);
             }
         }
 
-        SymbolTables { data }
+        Ok(SymbolTables { data })
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.data.iter().any(|symtab| symtab.truncated)
+    }
+
+    // Cross-link ifunc symbols with IRELATIVE relocations of their resolvers
+    pub fn link_irelative(&mut self, relocs: &RelocationSections) {
+        let irelative = relocs.irelative();
+
+        for symtab in &mut self.data {
+            symtab.irelative = irelative.clone();
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        let tables: Vec<String> = self
+            .data
+            .iter()
+            .map(|symtab| {
+                let symbols: Vec<String> = symtab
+                    .data
+                    .iter()
+                    .enumerate()
+                    .map(|(index, sym)| {
+                        json::object(&[
+                            ("index", index.to_string()),
+                            ("name", json::string(&symtab.strtab.get(sym.st_name as u64))),
+                            ("value", json::hex(sym.st_value)),
+                            ("size", json::hex(sym.st_size)),
+                            ("type", json::string(&format!("{:?}", sym.st_type))),
+                            ("bind", json::string(&format!("{:?}", sym.st_bind))),
+                            ("visibility", json::string(&format!("{:?}", sym.st_vis))),
+                            ("shndx", symtab.section_index(index).to_string()),
+                        ])
+                    })
+                    .collect();
+
+                json::object(&[
+                    ("name", json::string(&symtab.name)),
+                    ("symbols", json::array(&symbols)),
+                ])
+            })
+            .collect();
+
+        json::array(&tables)
+    }
+
+    pub fn set_filter(&mut self, filter: Option<SymbolFilter>) {
+        for symtab in &mut self.data {
+            symtab.filter = filter;
+        }
+    }
+
+    pub fn set_width(&mut self, width: Option<usize>) {
+        for symtab in &mut self.data {
+            symtab.width = width;
+        }
+    }
+
+    pub fn set_demangle(&mut self, demangle: bool) {
+        for symtab in &mut self.data {
+            symtab.demangle = demangle;
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.data.iter().flat_map(|symtab| symtab.names()).collect()
+    }
+
+    // Name of the symbol covering `address' and offset of the address
+    // from the symbol start, symbols with zero size cover only their
+    // own address; of the overlapping symbols the one with the highest
+    // binding priority and then the closest start is used
+    pub fn symbol_at(&self, address: u64) -> Option<(String, u64)> {
+        let mut best: Option<(&SymbolTable, &Symbol)> = None;
+
+        for symtab in &self.data {
+            for sym in symtab.data.iter().skip(1) {
+                match sym.st_type {
+                    SymbolType::Section | SymbolType::File => continue,
+                    _ if !sym.is_defined() => continue,
+                    _ => {}
+                }
+
+                let covers = address == sym.st_value
+                    || (address > sym.st_value && address - sym.st_value < sym.st_size);
+
+                if !covers {
+                    continue;
+                }
+
+                let better = match best {
+                    Some((_, current)) => {
+                        (sym.st_bind.priority(), sym.st_value)
+                            > (current.st_bind.priority(), current.st_value)
+                    }
+                    None => true,
+                };
+
+                if better {
+                    best = Some((symtab, sym));
+                }
+            }
+        }
+
+        best.map(|(symtab, sym)| {
+            (
+                symtab.strtab.get(sym.st_name as u64),
+                address - sym.st_value,
+            )
+        })
+    }
+
+    // Name and number of symbols of each table
+    pub fn counts(&self) -> Vec<(String, usize)> {
+        self.data
+            .iter()
+            .map(|symtab| (symtab.name.clone(), symtab.len()))
+            .collect()
+    }
+
+    // Same output as Display, but symbols are written as they are read
+    // so that huge tables are never held decoded all at once
+    pub fn write<W: Write>(
+        headers: &SectionHeaders,
+        file: &ElfFileHeader,
+        reader: &mut Reader,
+        out: &mut W,
+    ) -> Result<()> {
+        let sections = section_names(headers, file);
+
+        for (symtab_index, header) in headers.headers.iter().enumerate() {
+            if header.sh_type != SectionHeaderType::DynSym
+                && header.sh_type != SectionHeaderType::Symtab
+            {
+                continue;
+            }
+
+            // the extended indices are small compared to the symbols
+            let shndx = SymbolTable::read_shndx(headers, symtab_index, reader)?;
+            let size = reader.available(header.sh_offset, header.sh_size);
+            let count = match header.entsize() {
+                0 => 0,
+                entsize => size / entsize,
+            };
+
+            writeln!(
+                out,
+                "Symbol table `{}` contains {} entries:",
+                headers.strtab.get(header.sh_name as u64),
+                count
+            )?;
+            writeln!(out, "{}", SYMBOL_TABLE_COLUMNS)?;
+
+            SymbolTable::for_each(headers, header, reader, |index, name, sym| {
+                let shndx = resolve_shndx(sym, shndx.get(index));
+                let row = SymbolRow {
+                    index,
+                    name,
+                    sym,
+                    machine: file.e_machine,
+                    shndx,
+                    section: value_section(&sections, sym, shndx),
+                    note: "",
+                };
+                write!(out, "{}", row)?;
+                Ok(())
+            })?;
+
+            if size < header.sh_size {
+                writeln!(out, "file truncated after {} entries", count)?;
+            }
+            writeln!(out)?;
+        }
+
+        Ok(())
+    }
+}
+
+// Dynamic symbol table, symbols with index below `symoffset' of the
+// .gnu.hash table cannot be found by the dynamic linker through the hash
+#[derive(Debug)]
+pub struct DynamicSymbols {
+    symtab: Option<SymbolTable>,
+    symoffset: Option<u32>,
+    // Version name of each symbol and whether it is the default version
+    versions: Vec<Option<(String, bool)>>,
+}
+
+// Undefined symbols from the dynamic symbol table together with the
+// version and library they are expected from
+#[derive(Debug)]
+pub struct ImportedSymbols {
+    data: Vec<(String, Option<(String, String)>)>,
+}
+
+// Dynamic symbols with the given name and versions they bind to
+#[derive(Debug)]
+pub struct SymbolVersions {
+    name: String,
+    // Version name and whether it is the default version
+    data: Vec<Option<(String, bool)>>,
+}
+
+// Symbols listed the same way as `nm' does, sorted by name
+#[derive(Debug)]
+pub struct NmSymbols {
+    // Value (none for undefined symbols), class letter and name
+    data: Vec<(Option<u64>, char, String)>,
+}
+
+// Symbols of .symtab and .dynsym joined by name and value
+#[derive(Debug)]
+pub struct SymbolXref {
+    // Value and name of symbols present in both tables
+    both: Vec<(u64, String)>,
+    symtab_only: Vec<(u64, String)>,
+    dynsym_only: Vec<(u64, String)>,
+}
+
+impl ExportedSymbols {
+    pub fn new(
+        headers: &SectionHeaders,
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<ExportedSymbols> {
+        let mut data = vec![];
+
+        if let Some(header) = headers.get(SectionHeaderType::DynSym) {
+            let symtab = SymbolTable::new(headers, &header, machine, reader)?;
+
+            for index in 0..symtab.data.len() {
+                let (name, sym) = symtab.get_by_index(index);
+
+                if sym.is_exported() {
+                    data.push((name, sym));
+                }
+            }
+        }
+
+        data.sort_by(|a, b| a.0.cmp(&b.0));
+
+        Ok(ExportedSymbols {
+            data,
+            demangle: false,
+        })
+    }
+
+    pub fn set_demangle(&mut self, demangle: bool) {
+        self.demangle = demangle;
+    }
+}
+
+// Read index of the first symbol covered by the .gnu.hash table, the
+// header consists of nbuckets, symoffset, bloom_size and bloom_shift
+fn gnu_hash_symoffset(headers: &SectionHeaders, reader: &mut Reader) -> Result<Option<u32>> {
+    let header = match headers.get(SectionHeaderType::GnuHash) {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+
+    reader.check_range(header.sh_offset, 16)?;
+    reader.seek(SeekFrom::Start(header.sh_offset + 4))?;
+
+    Ok(Some(reader.read_u32::<LittleEndian>()?))
+}
+
+impl DynamicSymbols {
+    pub fn new(
+        headers: &SectionHeaders,
+        prheaders: &ProgramHeaders,
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<DynamicSymbols> {
+        let symtab = headers
+            .get(SectionHeaderType::DynSym)
+            .map(|header| SymbolTable::new(headers, &header, machine, reader))
+            .transpose()?;
+
+        // broken version tables leave the symbols without versions, the
+        // symbols themselves are still worth displaying
+        let count = symtab.as_ref().map_or(0, |symtab| symtab.data.len() as u64);
+        let versions = symbol_versions(headers, prheaders, count, reader).unwrap_or_default();
+        let verneed = VersionSection::new(headers, prheaders, reader).unwrap_or(None);
+        let verdef = VersionDefSection::new(headers, prheaders, reader).unwrap_or(None);
+
+        let versions = match &symtab {
+            Some(symtab) => symtab
+                .data
+                .iter()
+                .enumerate()
+                .map(|(index, sym)| {
+                    let version = versions.get(index).cloned().unwrap_or(0);
+                    symbol_version(version, sym, verneed.as_ref(), verdef.as_ref())
+                })
+                .collect(),
+            None => vec![],
+        };
+
+        Ok(DynamicSymbols {
+            symtab,
+            symoffset: gnu_hash_symoffset(headers, reader)?,
+            versions,
+        })
+    }
+
+    pub fn set_demangle(&mut self, demangle: bool) {
+        if let Some(symtab) = &mut self.symtab {
+            symtab.demangle = demangle;
+        }
+    }
+}
+
+// Name of the version from .gnu.version entry `version' of the symbol and
+// whether it is the default version
+fn symbol_version(
+    version: u16,
+    sym: &Symbol,
+    verneed: Option<&VersionSection>,
+    verdef: Option<&VersionDefSection>,
+) -> Option<(String, bool)> {
+    // index 0 is local and 1 the base (unversioned) global symbol,
+    // the top bit marks hidden (non-default) version
+    let hidden = version & 0x8000 != 0;
+
+    let version = match version & 0x7fff {
+        0 | 1 => None,
+        index if sym.is_defined() => verdef.and_then(|verdef| verdef.definition(index)),
+        index => verneed
+            .and_then(|verneed| verneed.requirement(index))
+            .map(|(version, _)| version),
+    };
+
+    // references are never default, only definitions can be
+    version.map(|version| (version, sym.is_defined() && !hidden))
+}
+
+impl ImportedSymbols {
+    pub fn new(
+        headers: &SectionHeaders,
+        prheaders: &ProgramHeaders,
+        machine: u16,
+        reader: &mut Reader,
+    ) -> Result<ImportedSymbols> {
+        let mut data = vec![];
+
+        let header = match headers.get(SectionHeaderType::DynSym) {
+            Some(header) => header,
+            None => return Ok(ImportedSymbols { data }),
+        };
+
+        let symtab = SymbolTable::new(headers, &header, machine, reader)?;
+        let count = symtab.data.len() as u64;
+        let versions = symbol_versions(headers, prheaders, count, reader)?;
+        let verneed = VersionSection::new(headers, prheaders, reader)?;
+
+        // the first symbol is always the undefined null symbol
+        for index in 1..symtab.data.len() {
+            let (name, sym) = symtab.get_by_index(index);
+
+            if sym.is_defined() {
+                continue;
+            }
+
+            // the top bit marks hidden symbol
+            let requirement = match (versions.get(index), &verneed) {
+                (Some(version), Some(verneed)) => verneed.requirement(version & 0x7fff),
+                _ => None,
+            };
+
+            data.push((name, requirement));
+        }
+
+        Ok(ImportedSymbols { data })
+    }
+}
+
+impl SymbolVersions {
+    pub fn new(
+        headers: &SectionHeaders,
+        prheaders: &ProgramHeaders,
+        machine: u16,
+        name: &str,
+        reader: &mut Reader,
+    ) -> Result<SymbolVersions> {
+        let mut data = vec![];
+
+        let header = match headers.get(SectionHeaderType::DynSym) {
+            Some(header) => header,
+            None => {
+                return Ok(SymbolVersions {
+                    name: name.to_string(),
+                    data,
+                })
+            }
+        };
+
+        let symtab = SymbolTable::new(headers, &header, machine, reader)?;
+        let count = symtab.data.len() as u64;
+        let versions = symbol_versions(headers, prheaders, count, reader)?;
+        let verneed = VersionSection::new(headers, prheaders, reader)?;
+        let verdef = VersionDefSection::new(headers, prheaders, reader)?;
+
+        for index in 1..symtab.data.len() {
+            let (symname, sym) = symtab.get_by_index(index);
+
+            if symname != name {
+                continue;
+            }
+
+            let version = versions.get(index).cloned().unwrap_or(0);
+
+            data.push(symbol_version(
+                version,
+                &sym,
+                verneed.as_ref(),
+                verdef.as_ref(),
+            ));
+        }
+
+        Ok(SymbolVersions {
+            name: name.to_string(),
+            data,
+        })
+    }
+}
+
+// Letter `nm' uses for the symbol, lowercase for local symbols
+fn nm_class(sym: &Symbol, section: Option<(&SectionHeader, String)>) -> char {
+    let weak = matches!(sym.st_bind, SymbolBinding::Weak);
+    let object = matches!(sym.st_type, SymbolType::Object);
+
+    match sym.st_shndx {
+        SHN_COMMON => return 'C',
+        SHN_UNDEF if weak && object => return 'v',
+        SHN_UNDEF if weak => return 'w',
+        SHN_UNDEF => return 'U',
+        _ => {}
+    }
+
+    match (&sym.st_type, &sym.st_bind) {
+        (SymbolType::GnuIndFun, _) => return 'i',
+        (_, SymbolBinding::Weak) if object => return 'V',
+        (_, SymbolBinding::Weak) => return 'W',
+        (_, SymbolBinding::GnuUnique) => return 'u',
+        (_, SymbolBinding::Unknown(_)) => return '?',
+        _ => {}
+    }
+
+    let class = match (sym.st_shndx, section) {
+        (SHN_ABS, _) => 'a',
+        (_, Some((_, name))) if name.starts_with(".debug") => 'N',
+        (_, Some((header, _))) => {
+            if header.sh_flags & SHF_EXECINSTR != 0 {
+                't'
+            } else if header.sh_flags & SHF_ALLOC == 0 {
+                'n'
+            } else if header.sh_type == SectionHeaderType::Bss {
+                'b'
+            } else if header.sh_flags & SHF_WRITE != 0 {
+                'd'
+            } else {
+                'r'
+            }
+        }
+        (_, None) => return '?',
+    };
+
+    match sym.st_bind {
+        SymbolBinding::Global => class.to_ascii_uppercase(),
+        _ => class,
+    }
+}
+
+impl NmSymbols {
+    // `nm' reads only .symtab, the dynamic symbol table is used for
+    // stripped files instead of reporting no symbols
+    pub fn new(headers: &SectionHeaders, machine: u16, reader: &mut Reader) -> Result<NmSymbols> {
+        let find = |sh_type: SectionHeaderType| {
+            headers
+                .headers
+                .iter()
+                .position(|header| header.sh_type == sh_type)
+        };
+
+        let index =
+            match find(SectionHeaderType::Symtab).or_else(|| find(SectionHeaderType::DynSym)) {
+                Some(index) => index,
+                None => return Ok(NmSymbols { data: vec![] }),
+            };
+
+        let mut symtab = SymbolTable::new(headers, &headers.headers[index], machine, reader)?;
+        symtab.shndx = SymbolTable::read_shndx(headers, index, reader)?;
+
+        let mut data = vec![];
+
+        for (index, sym) in symtab.data.iter().enumerate().skip(1) {
+            // section and file symbols are not listed by `nm'
+            match sym.st_type {
+                SymbolType::Section | SymbolType::File => continue,
+                _ => {}
+            }
+
+            let shndx = symtab.section_index(index) as usize;
+            let section = headers
+                .headers
+                .get(shndx)
+                .map(|header| (header, headers.strtab.get(header.sh_name as u64)));
+
+            let value = match sym.st_shndx {
+                SHN_UNDEF => None,
+                _ => Some(sym.st_value),
+            };
+
+            data.push((
+                value,
+                nm_class(sym, section),
+                symtab.strtab.get(sym.st_name as u64),
+            ));
+        }
+
+        data.sort_by(|a, b| (&a.2, a.0).cmp(&(&b.2, b.0)));
+
+        Ok(NmSymbols { data })
+    }
+}
+
+impl SymbolXref {
+    pub fn new(headers: &SectionHeaders, machine: u16, reader: &mut Reader) -> Result<SymbolXref> {
+        let mut read = |sh_type: SectionHeaderType| -> Result<BTreeSet<(String, u64)>> {
+            let mut symbols = BTreeSet::new();
+
+            let header = match headers.get(sh_type) {
+                Some(header) => header,
+                None => return Ok(symbols),
+            };
+
+            let symtab = SymbolTable::new(headers, &header, machine, reader)?;
+
+            for sym in symtab.data.iter().skip(1) {
+                match sym.st_type {
+                    SymbolType::Section | SymbolType::File => continue,
+                    _ => {}
+                }
+
+                let name = symtab.strtab.get(sym.st_name as u64);
+
+                // .symtab names of imported symbols carry the version
+                let name = match name.find('@') {
+                    Some(end) => name[..end].to_string(),
+                    None => name,
+                };
+
+                if !name.is_empty() {
+                    symbols.insert((name, sym.st_value));
+                }
+            }
+
+            Ok(symbols)
+        };
+
+        let symtab = read(SectionHeaderType::Symtab)?;
+        let dynsym = read(SectionHeaderType::DynSym)?;
+
+        let swap = |set: BTreeSet<&(String, u64)>| -> Vec<(u64, String)> {
+            set.into_iter()
+                .map(|(name, value)| (*value, name.clone()))
+                .collect()
+        };
+
+        Ok(SymbolXref {
+            both: swap(symtab.intersection(&dynsym).collect()),
+            symtab_only: swap(symtab.difference(&dynsym).collect()),
+            dynsym_only: swap(dynsym.difference(&symtab).collect()),
+        })
+    }
+}
+
+impl fmt::Display for SymbolXref {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let groups = [
+            ("Symbols in both .symtab and .dynsym", &self.both),
+            ("Symbols only in .symtab", &self.symtab_only),
+            ("Symbols only in .dynsym", &self.dynsym_only),
+        ];
+
+        for (title, symbols) in groups.iter() {
+            writeln!(f, "{} ({}):", title, symbols.len())?;
+
+            for (value, name) in symbols.iter() {
+                writeln!(f, "    {:#018x} {}", value, name)?;
+            }
+
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for NmSymbols {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (value, class, name) in &self.data {
+            match value {
+                Some(value) => writeln!(f, "{:016x} {} {}", value, class, name)?,
+                None => writeln!(f, "{:16} {} {}", "", class, name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for SymbolVersions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.data.is_empty() {
+            return writeln!(
+                f,
+                "Symbol `{}' not found in the dynamic symbol table",
+                self.name
+            );
+        }
+
+        for version in &self.data {
+            match version {
+                Some((version, true)) => writeln!(f, "{}@@{}", self.name, version)?,
+                Some((version, false)) => writeln!(f, "{}@{}", self.name, version)?,
+                None => writeln!(f, "{}", self.name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ImportedSymbols {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Imported symbols contains {} entries:", self.data.len())?;
+        writeln!(f, "{:<32} {:<16} Library", "Name", "Version")?;
+
+        for (name, requirement) in &self.data {
+            match requirement {
+                Some((version, file)) => writeln!(f, "{:<32} {:<16} {}", name, version, file)?,
+                None => writeln!(f, "{}", name)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ExportedSymbols {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Exported symbols contains {} entries:", self.data.len())?;
+        writeln!(
+            f,
+            "{:<16} {:<8} {:<8} {:<6} Name",
+            "Value", "Size", "Type", "Bind"
+        )?;
+
+        for (name, sym) in &self.data {
+            let typ = format!("{:?}", sym.st_type);
+            let bin = format!("{:?}", sym.st_bind);
+            let name = if self.demangle {
+                demangle(Cow::Borrowed(name))
+            } else {
+                Cow::Borrowed(name.as_str())
+            };
+
+            writeln!(
+                f,
+                "{:#016x} {:#08x} {:<8} {:<6} {}",
+                sym.st_value, sym.st_size, typ, bin, name
+            )?;
+        }
+        Ok(())
     }
 }
 
@@ -262,36 +1312,477 @@ impl fmt::Display for SymbolTables {
 
 impl fmt::Display for SymbolTable {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // the null symbol at index 0 is not a real reference
+        let listed = |index: usize, sym: &Symbol| match self.filter {
+            Some(filter) => index != 0 && filter.matches(sym),
+            None => true,
+        };
+
+        match self.filter {
+            Some(filter) => writeln!(
+                f,
+                "Symbol table `{}` contains {} entries, {} {}:",
+                self.name,
+                self.data.len(),
+                self.data
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, sym)| listed(*index, sym))
+                    .count(),
+                filter.describe()
+            )?,
+            None => writeln!(
+                f,
+                "Symbol table `{}` contains {} entries:",
+                self.name,
+                self.data.len()
+            )?,
+        }
+        writeln!(f, "{}", SYMBOL_TABLE_COLUMNS)?;
+
+        for (index, sym) in self.data.iter().enumerate() {
+            if !listed(index, sym) {
+                continue;
+            }
+
+            let name = if self.demangle {
+                sym.demangled_name(&self.strtab)
+            } else {
+                self.strtab.get_str(sym.st_name as u64)
+            };
+            let shndx = self.section_index(index);
+            let section = value_section(&self.sections, sym, shndx);
+            let note = match sym.st_type {
+                SymbolType::GnuIndFun => self.ifunc_note(sym.st_value),
+                _ => String::new(),
+            };
+            let row = SymbolRow {
+                index,
+                name: &truncated_name(&name, self.width),
+                sym,
+                machine: self.machine,
+                shndx,
+                section,
+                note: &note,
+            };
+
+            row.fmt(f)?;
+        }
+
+        if self.truncated {
+            writeln!(f, "file truncated after {} entries", self.data.len())?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for DynamicSymbols {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let symtab = match &self.symtab {
+            Some(symtab) => symtab,
+            None => return writeln!(f, "There is no dynamic symbol table"),
+        };
+
         writeln!(
             f,
             "Symbol table `{}` contains {} entries:",
-            self.name,
-            self.data.len()
-        )?;
-        writeln!(
-            f,
-            "{:<6} {:<16} {:<8} {:<8} {:<6} {:<9} {:<3} Name",
-            "Num", "Value", "Size", "Type", "Bind", "Vis", "Ndx"
+            symtab.name,
+            symtab.data.len()
         )?;
 
-        for (i, sym) in self.data.iter().enumerate() {
-            let name = self.strtab.get(sym.st_name as u64);
-            let typ = format!("{:?}", sym.st_type);
-            let bin = format!("{:?}", sym.st_bind);
-            let vis = format!("{:?}", sym.st_vis);
+        if let Some(symoffset) = self.symoffset {
+            writeln!(
+                f,
+                "Symbols from index {} are reachable through .gnu.hash",
+                symoffset
+            )?;
+        }
 
-            let ndx = if sym.st_shndx == 65521 {
-                String::from("Und")
+        writeln!(f, "{}", SYMBOL_TABLE_COLUMNS)?;
+
+        for (index, sym) in symtab.data.iter().enumerate() {
+            let name = if symtab.demangle {
+                sym.demangled_name(&symtab.strtab).into_owned()
             } else {
-                format!("{:03}", sym.st_shndx)
+                symtab.strtab.get(sym.st_name as u64)
+            };
+            // symbols defining the version itself have the version's name
+            let name = match self.versions.get(index) {
+                Some(Some((version, _))) if *version == name => name,
+                Some(Some((version, true))) => format!("{}@@{}", name, version),
+                Some(Some((version, false))) => format!("{}@{}", name, version),
+                _ => name,
+            };
+            let note = match self.symoffset {
+                Some(symoffset) if index < symoffset as usize && index != 0 => " (not hashed)",
+                _ => "",
             };
 
-            writeln!(
-                f,
-                "{:<06} {:#016x} {:#08x} {:<8} {:<6} {:9} {:3} {}",
-                i, sym.st_value, sym.st_size, typ, bin, vis, ndx, name
-            )?;
+            let row = SymbolRow {
+                index,
+                name: &name,
+                sym,
+                machine: symtab.machine,
+                shndx: symtab.section_index(index),
+                section: None,
+                note,
+            };
+
+            row.fmt(f)?;
         }
         Ok(())
     }
 }
+
+impl<'a> fmt::Display for SymbolRow<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sym = self.sym;
+        let typ = format!("{:?}", sym.st_type);
+        let bin = format!("{:?}", sym.st_bind);
+        let mut vis = format!("{:?}", sym.st_vis);
+
+        // PPC64
+        if self.machine == 21 && sym.ppc64_local_entry_offset() != 0 {
+            vis = format!("{} [localentry: {}]", vis, sym.ppc64_local_entry_offset());
+        }
+
+        let ndx = if self.shndx == 65521 {
+            String::from("Und")
+        } else {
+            format!("{:03}", self.shndx)
+        };
+
+        let value = match self.section {
+            Some(section) => format!("{}+{:#x}", section, sym.st_value),
+            None => format!("{:#016x}", sym.st_value),
+        };
+
+        writeln!(
+            f,
+            "{:<06} {:<16} {:#08x} {:<8} {:<6} {:9} {:3} {}{}",
+            self.index, value, sym.st_size, typ, bin, vis, ndx, self.name, self.note
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, symbol, ElfBuilder, Section};
+
+    // SHT_SYMTAB
+    const SYMTAB: u32 = 2;
+    // SHT_STRTAB
+    const STRTAB: u32 = 3;
+
+    fn symtab(entsize: u64, size: Option<u64>) -> Vec<u8> {
+        let mut data = symbol(0, 0, 0, 0, 0);
+        data.extend(symbol(1, 0x12, 1, 0x1000, 16));
+
+        let mut symtab = Section::new(".symtab", SYMTAB, data);
+        symtab.sh_link = 2;
+        symtab.sh_entsize = entsize;
+        symtab.sh_size = size;
+
+        // REL, x86-64
+        ElfBuilder::new(1, 62)
+            .section(symtab)
+            .section(Section::new(".strtab", STRTAB, b"\0main\0".to_vec()))
+            .build()
+    }
+
+    #[test]
+    fn reads_symbols() {
+        let (header, mut reader) = parse(symtab(24, None));
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let symbols = SymbolTables::new(&sections, &header, &mut reader).unwrap();
+
+        assert_eq!(symbols.names(), vec!["", "main"]);
+        assert!(!symbols.is_truncated());
+    }
+
+    #[test]
+    fn rejects_small_entsize() {
+        // entries would overlap and reads run past the end of the table
+        let (header, mut reader) = parse(symtab(1, Some(100)));
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+
+        assert!(SymbolTables::new(&sections, &header, &mut reader).is_err());
+    }
+
+    #[test]
+    fn string_table_out_of_bounds() {
+        let mut reader = Reader::new(b"\0main\0".to_vec());
+
+        assert_eq!(StringTable::read(0, 6, &mut reader).unwrap().get(1), "main");
+        assert!(StringTable::read(4, 6, &mut reader).unwrap_err().is_eof());
+        assert!(StringTable::read(u64::MAX, 2, &mut reader).is_err());
+    }
+
+    #[test]
+    fn bad_string_offset() {
+        let mut reader = Reader::new(b"\0main\0".to_vec());
+        let strtab = StringTable::read(0, 6, &mut reader).unwrap();
+
+        assert_eq!(strtab.get(1), "main");
+        assert_eq!(strtab.get(6), "");
+        assert_eq!(strtab.get(100), "<bad offset>");
+        assert_eq!(strtab.get_str(u64::MAX), "<bad offset>");
+        assert!(matches!(
+            strtab.try_get(100),
+            Err(ElfError::BadStringOffset(100))
+        ));
+    }
+
+    #[test]
+    fn truncated_table() {
+        // the section claims more entries than the file holds
+        let (header, mut reader) = parse(symtab(24, Some(0x10000)));
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let symbols = SymbolTables::new(&sections, &header, &mut reader).unwrap();
+
+        assert!(symbols.is_truncated());
+    }
+
+    #[test]
+    fn streams_truncated_table() {
+        let (header, mut reader) = parse(symtab(24, Some(0x10000)));
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let symbols = SymbolTables::new(&sections, &header, &mut reader).unwrap();
+
+        let mut out = vec![];
+        SymbolTables::write(&sections, &header, &mut reader, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("file truncated after "), "{}", out);
+        assert_eq!(out, format!("{}", symbols));
+    }
+
+    #[test]
+    fn streams_without_string_table() {
+        let (header, mut reader) = parse(symtab(24, None));
+        let mut sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        sections.headers[1].sh_link = 100;
+
+        let mut names = vec![];
+        SymbolTable::for_each(
+            &sections,
+            &sections.headers[1],
+            &mut reader,
+            |_, name, _| {
+                names.push(name.to_string());
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(names.len(), 2);
+    }
+
+    #[test]
+    fn extended_section_index() {
+        // SHT_SYMTAB_SHNDX
+        const SYMTAB_SHNDX: u32 = 18;
+
+        // only the second symbol has its index in the extended table
+        let mut data = symbol(0, 0, 0, 0, 0);
+        data.extend(symbol(1, 0x12, 1, 0x1000, 16));
+        data.extend(symbol(6, 0x11, SHN_XINDEX, 0x2000, 8));
+
+        let mut symtab = Section::new(".symtab", SYMTAB, data);
+        symtab.sh_link = 2;
+        symtab.sh_entsize = 24;
+
+        let mut shndx = Section::new(".symtab_shndx", SYMTAB_SHNDX, vec![]);
+        shndx.sh_link = 1;
+        for index in &[0u32, 0, 3] {
+            shndx.data.extend(&index.to_le_bytes());
+        }
+
+        let data = ElfBuilder::new(1, 62)
+            .section(symtab)
+            .section(Section::new(".strtab", STRTAB, b"\0main\0data\0".to_vec()))
+            .section(shndx)
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let symbols = SymbolTables::new(&sections, &header, &mut reader).unwrap();
+
+        assert_eq!(symbols.data[0].section_index(1), 1);
+        assert_eq!(symbols.data[0].section_index(2), 3);
+
+        // the streaming output has the same index
+        let mut out = vec![];
+        SymbolTables::write(&sections, &header, &mut reader, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        let row = out.lines().find(|line| line.ends_with(" data")).unwrap();
+        assert!(row.contains(" 003 "), "{}", row);
+    }
+
+    #[test]
+    fn streams_section_relative_values() {
+        let (header, mut reader) = parse(symtab(24, None));
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let symbols = SymbolTables::new(&sections, &header, &mut reader).unwrap();
+
+        let mut out = vec![];
+        SymbolTables::write(&sections, &header, &mut reader, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains(" .symtab+0x1000 "), "{}", out);
+        assert_eq!(out, format!("{}", symbols));
+    }
+
+    #[test]
+    fn nm_letters() {
+        // SHT_PROGBITS, SHT_NOBITS
+        const PROGBITS: u32 = 1;
+        const NOBITS: u32 = 8;
+
+        let symbols = [
+            // name, info, shndx
+            ("T", 0x12, 3),
+            ("t", 0x02, 3),
+            ("D", 0x11, 4),
+            ("b", 0x01, 5),
+            ("R", 0x11, 6),
+            ("U", 0x10, SHN_UNDEF),
+            ("w", 0x22, SHN_UNDEF),
+            ("v", 0x21, SHN_UNDEF),
+            ("W", 0x22, 3),
+            ("V", 0x21, 4),
+            ("A", 0x10, SHN_ABS),
+            ("C", 0x11, SHN_COMMON),
+            ("i", 0x1a, 3),
+        ];
+
+        let mut data = symbol(0, 0, 0, 0, 0);
+        let mut strtab = vec![0];
+
+        for (name, info, shndx) in &symbols {
+            data.extend(symbol(strtab.len() as u32, *info, *shndx, 0, 0));
+            strtab.extend(name.as_bytes());
+            strtab.push(0);
+        }
+
+        let mut symtab = Section::new(".symtab", SYMTAB, data);
+        symtab.sh_link = 2;
+        symtab.sh_entsize = 24;
+
+        let section = |name, sh_type, sh_flags| {
+            let mut section = Section::new(name, sh_type, vec![0; 16]);
+            section.sh_flags = sh_flags;
+            section
+        };
+
+        let data = ElfBuilder::new(1, 62)
+            .section(symtab)
+            .section(Section::new(".strtab", STRTAB, strtab))
+            .section(section(".text", PROGBITS, SHF_ALLOC | SHF_EXECINSTR))
+            .section(section(".data", PROGBITS, SHF_ALLOC | SHF_WRITE))
+            .section(section(".bss", NOBITS, SHF_ALLOC | SHF_WRITE))
+            .section(section(".rodata", PROGBITS, SHF_ALLOC))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let nm = NmSymbols::new(&sections, 62, &mut reader).unwrap();
+
+        assert_eq!(nm.data.len(), symbols.len());
+        for (_, class, name) in &nm.data {
+            assert_eq!(name, &class.to_string());
+        }
+    }
+
+    #[cfg(feature = "demangle")]
+    #[test]
+    fn demangles_names() {
+        let mut reader = Reader::new(
+            b"\0_ZN4core3fmt5write17h01234567890abcdeE\0_ZN3foo3barEv\0main\0".to_vec(),
+        );
+        let strtab = StringTable::read(0, reader.len(), &mut reader).unwrap();
+
+        let name = |st_name| {
+            let mut sym = Symbol::null();
+            sym.st_name = st_name;
+            sym.demangled_name(&strtab).into_owned()
+        };
+
+        assert_eq!(name(1), "core::fmt::write");
+        assert_eq!(name(40), "foo::bar()");
+        assert_eq!(name(54), "main");
+        assert!(matches!(
+            Symbol::null().demangled_name(&strtab),
+            Cow::Borrowed("")
+        ));
+    }
+
+    #[test]
+    fn global_wins_over_weak() {
+        // local, weak and global symbols at the same address
+        let mut data = symbol(0, 0, 0, 0, 0);
+        data.extend(symbol(1, 0x02, 1, 0x1000, 16));
+        data.extend(symbol(5, 0x22, 1, 0x1000, 16));
+        data.extend(symbol(10, 0x12, 1, 0x1000, 16));
+        data.extend(symbol(5, 0x22, 1, 0x1000, 16));
+
+        let mut symtab = Section::new(".symtab", SYMTAB, data);
+        symtab.sh_link = 2;
+        symtab.sh_entsize = 24;
+
+        let strtab = Section::new(".strtab", STRTAB, b"\0loc\0weak\0global\0".to_vec());
+        // DYN, x86-64
+        let data = ElfBuilder::new(3, 62)
+            .section(symtab)
+            .section(strtab)
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let symbols = SymbolTables::new(&sections, &header, &mut reader).unwrap();
+
+        assert_eq!(symbols.symbol_at(0x1008), Some(("global".to_string(), 8)));
+        assert_eq!(symbols.symbol_at(0x1010), None);
+    }
+
+    #[test]
+    fn broken_version_table() {
+        // SHT_DYNSYM, SHT_GNU_verneed, SHT_GNU_versym
+        const DYNSYM: u32 = 11;
+        const VERNEED: u32 = 0x6ffffffe;
+        const VERSYM: u32 = 0x6fffffff;
+
+        let mut data = symbol(0, 0, 0, 0, 0);
+        data.extend(symbol(1, 0x12, 0, 0, 0));
+
+        let mut dynsym = Section::new(".dynsym", DYNSYM, data);
+        dynsym.sh_link = 2;
+        dynsym.sh_entsize = 24;
+
+        // the requirements run past the end of the file
+        let mut verneed = Section::new(".gnu.version_r", VERNEED, vec![0; 16]);
+        verneed.sh_link = 2;
+        verneed.sh_info = 1;
+        verneed.sh_size = Some(0x10000);
+
+        // DYN, x86-64
+        let data = ElfBuilder::new(3, 62)
+            .section(dynsym)
+            .section(Section::new(".dynstr", STRTAB, b"\0puts\0".to_vec()))
+            .section(Section::new(".gnu.version", VERSYM, vec![0, 0, 2, 0]))
+            .section(verneed)
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let segments = ProgramHeaders::new(&header, &mut reader).unwrap();
+
+        assert!(VersionSection::new(&sections, &segments, &mut reader).is_err());
+
+        let symbols = DynamicSymbols::new(&sections, &segments, 62, &mut reader).unwrap();
+
+        assert_eq!(symbols.versions, vec![None, None]);
+    }
+}
@@ -1,7 +1,10 @@
-use crate::reader::{LittleEndian, ReadBytesExt, Reader, Seek, SeekFrom};
+use crate::dynamic::{dynamic_strtab, DynamicSection};
+use crate::error::{ElfError, Result};
+use crate::json;
+use crate::program::ProgramHeaders;
+use crate::reader::{LittleEndian, ReadBytesExt, Reader, ReaderExt, Seek, SeekFrom};
 use crate::section::{SectionHeaderType, SectionHeaders};
 use crate::symbols::StringTable;
-use anyhow::{Result, Context};
 use std::fmt;
 
 #[derive(Debug)]
@@ -40,6 +43,41 @@ pub enum VersionAuxFlags {
     Unknown(u16),
 }
 
+#[derive(Debug)]
+pub struct VersionDef {
+    // Version of structure
+    version: u16,
+    // Version information
+    flags: VersionDefFlags,
+    // Version index as used by the version symbol table
+    index: u16,
+    // Number of associated aux entries
+    aux_count: u16,
+    // Hash value of the version name
+    hash: u32,
+    // Offset in bytes to verdaux array
+    aux_offset: u32,
+    // Offset in bytes to next VersionDef entry
+    next_offset: u32,
+}
+
+#[derive(Debug)]
+pub struct VersionDefAux {
+    // Version or dependency name string offset
+    name: u32,
+    // Offset in bytes to next VersionDefAux
+    next: u32,
+}
+
+#[derive(Debug)]
+pub enum VersionDefFlags {
+    None,
+    // Version definition of the file itself
+    Base,
+    Weak,
+    Unknown(u16),
+}
+
 #[derive(Debug)]
 pub struct VersionSection {
     data: Vec<(Vec<VersionAux>, VersionNeed)>,
@@ -49,6 +87,16 @@ pub struct VersionSection {
     name: String,
 }
 
+#[derive(Debug)]
+pub struct VersionDefSection {
+    // The first aux entry holds the version name, others name its parents
+    data: Vec<(Vec<VersionDefAux>, VersionDef)>,
+    // .dynamic string table used only for Display
+    strtab: StringTable,
+    // Name of the section acquired from sections strtab
+    name: String,
+}
+
 impl VersionNeed {
     fn new(reader: &mut Reader) -> Result<VersionNeed> {
         Ok(VersionNeed {
@@ -61,15 +109,72 @@ impl VersionNeed {
     }
 }
 
-impl VersionSection {
-    pub fn new(headers: &SectionHeaders, reader: &mut Reader) -> Result<Option<VersionSection>> {
+// File offset, size, number of entries and name of the version table,
+// files without section headers have it located through the dynamic
+// segment and it may take the rest of the file
+fn locate_table(
+    headers: &SectionHeaders,
+    prheaders: &ProgramHeaders,
+    sh_type: SectionHeaderType,
+    name: &str,
+    reader: &mut Reader,
+) -> Result<Option<(u64, u64, u64, String)>> {
+    if let Some(header) = headers.get(sh_type.clone()) {
+        reader.check_range(header.sh_offset, header.sh_size)?;
+
+        let name = headers.strtab.get(header.sh_name as u64);
+        return Ok(Some((
+            header.sh_offset,
+            header.sh_size,
+            header.sh_info as u64,
+            name,
+        )));
+    }
+
+    if !headers.headers.is_empty() {
+        return Ok(None);
+    }
+
+    let dynamic = match DynamicSection::new_from_segment(prheaders, reader)? {
+        Some(dynamic) => dynamic,
+        None => return Ok(None),
+    };
+
+    let table = match sh_type {
+        SectionHeaderType::GnuVerDef => dynamic.verdef(),
+        _ => dynamic.verneed(),
+    };
 
-        if headers.get(SectionHeaderType::GnuVerNeed).is_none() {
-            return Ok(None);
+    match table {
+        Some((addr, count)) => {
+            let offset = prheaders
+                .vaddr_to_offset(addr)
+                .ok_or_else(|| ElfError::Malformed(format!("{} address {:#x}", name, addr)))?;
+
+            let size = reader.len().saturating_sub(offset);
+
+            Ok(Some((offset, size, count, name.to_string())))
         }
+        None => Ok(None),
+    }
+}
 
-        let header = headers.get(SectionHeaderType::GnuVerNeed)
-                            .context("Unable to get version section")?;
+impl VersionSection {
+    pub fn new(
+        headers: &SectionHeaders,
+        prheaders: &ProgramHeaders,
+        reader: &mut Reader,
+    ) -> Result<Option<VersionSection>> {
+        let (start, _, count, name) = match locate_table(
+            headers,
+            prheaders,
+            SectionHeaderType::GnuVerNeed,
+            ".gnu.version_r",
+            reader,
+        )? {
+            Some(table) => table,
+            None => return Ok(None),
+        };
 
         let mut offset: u64 = 0;
         let mut data: Vec<(Vec<VersionAux>, VersionNeed)> = vec![];
@@ -77,18 +182,15 @@ impl VersionSection {
 
         let mut cnt = 0;
 
-        while cnt < header.sh_info {
-            reader
-                .seek(SeekFrom::Start(header.sh_offset + offset))
-                .unwrap();
+        while cnt < count {
+            reader.seek(SeekFrom::Start(start + offset))?;
 
             let verneed = VersionNeed::new(reader)?;
             let mut aux_offset: u64 = verneed.aux_offset as u64;
             let mut i = 0;
 
             while i < verneed.aux_count {
-                reader
-                    .seek(SeekFrom::Start(header.sh_offset + offset + aux_offset))?;
+                reader.seek(SeekFrom::Start(start + offset + aux_offset))?;
 
                 let au = VersionAux::new(reader)?;
 
@@ -104,13 +206,237 @@ impl VersionSection {
             cnt += 1;
         }
 
-        let strtab = headers.dynstr(reader)
-                            .context("Unable to load get dynamic string")?;
-        let name = headers.strtab.get(header.sh_name as u64);
+        let strtab = dynamic_strtab(headers, prheaders, reader)?
+            .ok_or(ElfError::MissingSection(".dynstr"))?;
 
         Ok(Some(VersionSection { data, strtab, name }))
     }
+
+    // Number of files the versions are needed from
+    pub fn count(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn to_json(&self) -> String {
+        let files: Vec<String> = self
+            .data
+            .iter()
+            .map(|(auxes, verneed)| {
+                let versions: Vec<String> = auxes
+                    .iter()
+                    .map(|aux| {
+                        json::object(&[
+                            ("name", json::string(&self.strtab.get(aux.name as u64))),
+                            ("index", aux.other.to_string()),
+                            ("flags", json::string(&format!("{:?}", aux.flags))),
+                            ("hash", json::hex(aux.hash as u64)),
+                        ])
+                    })
+                    .collect();
+
+                json::object(&[
+                    (
+                        "file",
+                        json::string(&self.strtab.get(verneed.file_offset as u64)),
+                    ),
+                    ("versions", json::array(&versions)),
+                ])
+            })
+            .collect();
+
+        json::array(&files)
+    }
+
+    // Find the version name and the file that provides version
+    // with the given index (as used by the version symbol table)
+    pub fn requirement(&self, index: u16) -> Option<(String, String)> {
+        for (auxes, verneed) in &self.data {
+            for aux in auxes {
+                if aux.other == index {
+                    let name = self.strtab.get(aux.name as u64);
+                    let file = self.strtab.get(verneed.file_offset as u64);
+
+                    return Some((name, file));
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl VersionDef {
+    fn new(reader: &mut Reader) -> Result<VersionDef> {
+        Ok(VersionDef {
+            version: reader.read_u16::<LittleEndian>()?,
+            flags: VersionDefFlags::new(reader.read_u16::<LittleEndian>()?),
+            index: reader.read_u16::<LittleEndian>()?,
+            aux_count: reader.read_u16::<LittleEndian>()?,
+            hash: reader.read_u32::<LittleEndian>()?,
+            aux_offset: reader.read_u32::<LittleEndian>()?,
+            next_offset: reader.read_u32::<LittleEndian>()?,
+        })
+    }
+}
+
+impl VersionDefAux {
+    fn new(reader: &mut Reader) -> Result<VersionDefAux> {
+        Ok(VersionDefAux {
+            name: reader.read_u32::<LittleEndian>()?,
+            next: reader.read_u32::<LittleEndian>()?,
+        })
+    }
+}
+
+impl VersionDefFlags {
+    fn new(value: u16) -> VersionDefFlags {
+        match value {
+            0x0 => VersionDefFlags::None,
+            0x1 => VersionDefFlags::Base,
+            0x2 => VersionDefFlags::Weak,
+            _ => VersionDefFlags::Unknown(value),
+        }
+    }
+}
+
+impl VersionDefSection {
+    pub fn new(
+        headers: &SectionHeaders,
+        prheaders: &ProgramHeaders,
+        reader: &mut Reader,
+    ) -> Result<Option<VersionDefSection>> {
+        let (start, size, count, name) = match locate_table(
+            headers,
+            prheaders,
+            SectionHeaderType::GnuVerDef,
+            ".gnu.version_d",
+            reader,
+        )? {
+            Some(table) => table,
+            None => return Ok(None),
+        };
+
+        let mut offset: u64 = 0;
+        let mut data: Vec<(Vec<VersionDefAux>, VersionDef)> = vec![];
+
+        // each entry takes at least 20 bytes, the count may be corrupted
+        for _ in 0..count.min(size / 20) {
+            if offset >= size {
+                break;
+            }
+
+            reader.seek(SeekFrom::Start(start + offset))?;
+
+            let verdef = VersionDef::new(reader)?;
+            let mut aux_offset: u64 = verdef.aux_offset as u64;
+            let mut aux = vec![];
+
+            for _ in 0..verdef.aux_count {
+                reader.seek(SeekFrom::Start(start + offset + aux_offset))?;
+
+                let au = VersionDefAux::new(reader)?;
+
+                aux_offset += au.next as u64;
+                aux.push(au);
+            }
+
+            let next_offset = verdef.next_offset as u64;
+            data.push((aux, verdef));
+
+            // the last entry has no next one
+            if next_offset == 0 {
+                break;
+            }
+
+            offset += next_offset;
+        }
+
+        let strtab = dynamic_strtab(headers, prheaders, reader)?
+            .ok_or(ElfError::MissingSection(".dynstr"))?;
+
+        Ok(Some(VersionDefSection { data, strtab, name }))
+    }
+
+    // The first aux entry is the name, the others are parents
+    pub fn to_json(&self) -> String {
+        let versions: Vec<String> = self
+            .data
+            .iter()
+            .map(|(auxes, verdef)| {
+                let names: Vec<String> = auxes
+                    .iter()
+                    .map(|aux| json::string(&self.strtab.get(aux.name as u64)))
+                    .collect();
+
+                json::object(&[
+                    ("index", verdef.index.to_string()),
+                    ("flags", json::string(&format!("{:?}", verdef.flags))),
+                    ("name", json::or_null(names.first().cloned())),
+                    ("parents", json::array(names.get(1..).unwrap_or(&[]))),
+                    ("hash", json::hex(verdef.hash as u64)),
+                ])
+            })
+            .collect();
+
+        json::array(&versions)
+    }
+
+    // Name of the version with the given index (as used by the version
+    // symbol table)
+    pub fn definition(&self, index: u16) -> Option<String> {
+        self.data
+            .iter()
+            .find(|(_, verdef)| verdef.index == index)
+            .and_then(|(aux, _)| aux.first())
+            .map(|aux| self.strtab.get(aux.name as u64))
+    }
+}
+
+// Read version indices of the dynamic symbols (.gnu.version section),
+// there is one entry for each of the `count' symbols in the dynamic symbol
+// table, files without section headers have it located through DT_VERSYM
+pub fn symbol_versions(
+    headers: &SectionHeaders,
+    prheaders: &ProgramHeaders,
+    count: u64,
+    reader: &mut Reader,
+) -> Result<Vec<u16>> {
+    let (start, size) = match headers.get(SectionHeaderType::GnuVerSym) {
+        Some(header) => (header.sh_offset, header.sh_size),
+        None if headers.headers.is_empty() => {
+            let addr = match DynamicSection::new_from_segment(prheaders, reader)? {
+                Some(dynamic) => dynamic.versym(),
+                None => None,
+            };
+
+            match addr {
+                Some(addr) => {
+                    let offset = prheaders.vaddr_to_offset(addr).ok_or_else(|| {
+                        ElfError::Malformed(format!(".gnu.version address {:#x}", addr))
+                    })?;
+
+                    (offset, count * 2)
+                }
+                None => return Ok(vec![]),
+            }
+        }
+        None => return Ok(vec![]),
+    };
+
+    reader.check_range(start, size)?;
+    reader.seek(SeekFrom::Start(start))?;
+
+    let mut versions = vec![];
+    let mut offset = 0;
+
+    while offset < size {
+        versions.push(reader.read_u16::<LittleEndian>()?);
+        offset += 2;
+    }
+
+    Ok(versions)
 }
+
 impl VersionAux {
     fn new(reader: &mut Reader) -> Result<VersionAux> {
         Ok(VersionAux {
@@ -165,3 +491,186 @@ impl fmt::Display for VersionSection {
         Ok(())
     }
 }
+
+impl fmt::Display for VersionDefSection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "Version definitions section `{}' contain {} entries",
+            self.name,
+            self.data.len()
+        )?;
+
+        for (auxes, verdef) in &self.data {
+            let name = match auxes.first() {
+                Some(aux) => self.strtab.get(aux.name as u64),
+                None => String::new(),
+            };
+
+            writeln!(
+                f,
+                "Version: {:<4} Index: {:<4} Name: {:<16} Flags: {:?} Hash: {:#08x}",
+                verdef.version, verdef.index, name, verdef.flags, verdef.hash
+            )?;
+
+            for aux in auxes.iter().skip(1) {
+                writeln!(f, "    Parent: {}", self.strtab.get(aux.name as u64))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixture::{parse, strip, ElfBuilder, Section, Segment};
+
+    // PT_LOAD, PT_DYNAMIC, SHT_STRTAB, SHT_GNU_verdef, SHT_GNU_versym,
+    // DT_VERSYM
+    const LOAD: u32 = 1;
+    const DYNAMIC: u32 = 2;
+    const STRTAB: u32 = 3;
+    const VERDEF: u32 = 0x6ffffffd;
+    const VERSYM: u32 = 0x6fffffff;
+    const DT_VERSYM: u64 = 0x6ffffff0;
+
+    fn versions(data: Vec<u8>, count: u64) -> Result<Vec<u16>> {
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader)?;
+        let segments = ProgramHeaders::new(&header, &mut reader)?;
+
+        symbol_versions(&sections, &segments, count, &mut reader)
+    }
+
+    fn versym() -> Vec<u8> {
+        [0u16, 1, 2, 0x8003]
+            .iter()
+            .flat_map(|version| version.to_le_bytes().to_vec())
+            .collect()
+    }
+
+    // Shared object with the version table in a loadable segment at
+    // 0x1000 and DT_VERSYM pointing to it
+    fn shared_object() -> Vec<u8> {
+        let mut load = Segment::new(LOAD, versym());
+        load.p_vaddr = 0x1000;
+
+        let mut dynamic = vec![];
+        for (tag, value) in &[(DT_VERSYM, 0x1000u64), (0, 0)] {
+            dynamic.extend(&tag.to_le_bytes());
+            dynamic.extend(&value.to_le_bytes());
+        }
+
+        let mut section = Section::new(".gnu.version", VERSYM, versym());
+        section.sh_entsize = 2;
+
+        // DYN, x86-64
+        ElfBuilder::new(3, 62)
+            .segment(load)
+            .segment(Segment::new(DYNAMIC, dynamic))
+            .section(section)
+            .build()
+    }
+
+    #[test]
+    fn reads_section() {
+        assert_eq!(versions(shared_object(), 4).unwrap(), vec![0, 1, 2, 0x8003]);
+    }
+
+    #[test]
+    fn stripped_reads_dynamic_segment() {
+        let data = strip(shared_object());
+        assert_eq!(versions(data.clone(), 4).unwrap(), vec![0, 1, 2, 0x8003]);
+        // the number of entries comes from the dynamic symbol table
+        assert_eq!(versions(data, 2).unwrap(), vec![0, 1]);
+    }
+
+    #[test]
+    fn stripped_without_versions() {
+        // DYN, x86-64
+        let data = strip(ElfBuilder::new(3, 62).build());
+
+        assert!(versions(data, 4).unwrap().is_empty());
+    }
+
+    // Elf64_Verdef followed by its Elf64_Verdaux entries (vd_aux is 20)
+    fn verdef(flags: u16, index: u16, names: &[u32], next: u32) -> Vec<u8> {
+        let mut data = vec![];
+
+        data.extend(&1u16.to_le_bytes());
+        data.extend(&flags.to_le_bytes());
+        data.extend(&index.to_le_bytes());
+        data.extend(&(names.len() as u16).to_le_bytes());
+        data.extend(&0u32.to_le_bytes());
+        data.extend(&20u32.to_le_bytes());
+        data.extend(&next.to_le_bytes());
+
+        for (i, name) in names.iter().enumerate() {
+            let next: u32 = if i + 1 < names.len() { 8 } else { 0 };
+
+            data.extend(&name.to_le_bytes());
+            data.extend(&next.to_le_bytes());
+        }
+
+        data
+    }
+
+    #[test]
+    fn reads_definitions() {
+        // the base version and FOO_1.0 with FOO_0.9 as its parent
+        let mut data = verdef(1, 1, &[1], 28);
+        data.extend(verdef(0, 2, &[11, 19], 0));
+
+        let mut section = Section::new(".gnu.version_d", VERDEF, data);
+        section.sh_link = 2;
+        section.sh_info = 2;
+
+        // DYN, x86-64
+        let data = ElfBuilder::new(3, 62)
+            .section(section)
+            .section(Section::new(
+                ".dynstr",
+                STRTAB,
+                b"\0libfoo.so\0FOO_1.0\0FOO_0.9\0".to_vec(),
+            ))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let segments = ProgramHeaders::new(&header, &mut reader).unwrap();
+        let verdef = VersionDefSection::new(&sections, &segments, &mut reader)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(verdef.definition(1), Some("libfoo.so".to_string()));
+        assert_eq!(verdef.definition(2), Some("FOO_1.0".to_string()));
+        assert_eq!(verdef.definition(3), None);
+        assert!(verdef.to_json().contains(r#""parents": ["FOO_0.9"]"#));
+    }
+
+    #[test]
+    fn self_referencing_definition() {
+        // vd_next of 0 with a count far past the section size
+        let mut section = Section::new(".gnu.version_d", VERDEF, verdef(1, 1, &[1], 0));
+        section.sh_link = 2;
+        section.sh_info = 0xffffffff;
+
+        // DYN, x86-64
+        let data = ElfBuilder::new(3, 62)
+            .section(section)
+            .section(Section::new(".dynstr", STRTAB, b"\0libfoo.so\0".to_vec()))
+            .build();
+
+        let (header, mut reader) = parse(data);
+        let sections = SectionHeaders::new(&header, &mut reader).unwrap();
+        let segments = ProgramHeaders::new(&header, &mut reader).unwrap();
+        let verdef = VersionDefSection::new(&sections, &segments, &mut reader)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(verdef.data.len(), 1);
+        assert_eq!(verdef.definition(1), Some("libfoo.so".to_string()));
+    }
+}
@@ -0,0 +1,41 @@
+// Inputs that made the fuzz target panic, they are parsed the same way
+// the target does and must be either rejected or displayed
+use rust_elf::file::ElfFileHeader;
+use rust_elf::reader::Reader;
+use rust_elf::section::SectionHeaders;
+use rust_elf::symbols::SymbolTables;
+use std::fs;
+use std::path::Path;
+
+fn parse(data: Vec<u8>) {
+    let mut reader = Reader::new(data);
+
+    let header = match ElfFileHeader::new(&mut reader) {
+        Ok(header) => header,
+        Err(_) => return,
+    };
+
+    let sections = match SectionHeaders::new(&header, &mut reader) {
+        Ok(sections) => sections,
+        Err(_) => return,
+    };
+
+    let _ = format!("{}", sections);
+
+    if let Ok(symbols) = SymbolTables::new(&sections, &header, &mut reader) {
+        let _ = format!("{}", symbols);
+    }
+}
+
+#[test]
+fn parse_regressions() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fuzz/regressions/parse");
+    let mut count = 0;
+
+    for entry in fs::read_dir(dir).unwrap() {
+        parse(fs::read(entry.unwrap().path()).unwrap());
+        count += 1;
+    }
+
+    assert!(count > 0);
+}